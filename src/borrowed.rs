@@ -0,0 +1,53 @@
+use axum_core::extract::{FromRequest, Request};
+use bytes::Bytes;
+use serde::Deserialize;
+
+use crate::{BcsConfig, BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// Extractor that keeps the raw BCS body so values can be decoded borrowing from it.
+///
+/// Types with `&[u8]` or `&str` fields are decoded with [`BcsBytes::decode`] without
+/// copying those fields out of the request body.
+pub struct BcsBytes {
+  bytes: Bytes,
+  config: BcsConfig,
+}
+
+impl BcsBytes {
+  pub(crate) fn new(bytes: Bytes, config: BcsConfig) -> Self {
+    BcsBytes { bytes, config }
+  }
+
+  /// Decodes the body with the limits of the route's [`BcsConfig`].
+  pub fn decode<'a, T>(&'a self) -> Result<T, BcsRejection>
+  where
+    T: Deserialize<'a>,
+  {
+    decode(&self.bytes, &self.config)
+  }
+
+  pub fn bytes(&self) -> &Bytes {
+    &self.bytes
+  }
+
+  pub fn into_bytes(self) -> Bytes {
+    self.bytes
+  }
+}
+
+impl<S> FromRequest<S> for BcsBytes
+where
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      Ok(BcsBytes::new(bytes, config))
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
@@ -0,0 +1,39 @@
+use axum_core::extract::{FromRequest, Request};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// Extractor that decodes like [`Bcs`](crate::Bcs) and rejects bodies that do not re-encode
+/// to the same bytes.
+///
+/// Types with custom `Deserialize` impls can accept several encodings of one value; this
+/// guarantees the body is the canonical one, e.g. before checking a signature over it.
+pub struct CanonicalBcs<T>(pub T);
+
+impl<S, T> FromRequest<S> for CanonicalBcs<T>
+where
+  T: Serialize + DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if !bcs_body_type(req.headers(), &config) {
+      return Err(BcsRejection::MissingContentType);
+    }
+
+    let bytes = read_body(req, _s, &config).await?;
+    let value = decode(&bytes, &config)?;
+    let encoded = bcs::to_bytes(&value)?;
+    if encoded != bytes {
+      let offset = encoded
+        .iter()
+        .zip(bytes.iter())
+        .position(|(a, b)| a != b)
+        .unwrap_or(encoded.len().min(bytes.len()));
+      return Err(BcsRejection::NonCanonical { offset });
+    }
+    Ok(CanonicalBcs(value))
+  }
+}
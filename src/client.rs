@@ -0,0 +1,86 @@
+use std::future::Future;
+
+use http::{HeaderValue, header};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::{APPLICATION_BCS, BcsConfig, BcsErrorBody, BcsRejection, bcs_content_type, decode};
+
+/// Errors from the `reqwest` helpers.
+///
+/// Problems with the BCS body itself are reported with the same [`BcsRejection`] variants the
+/// server-side extractors use, and rejections sent by a server as a BCS-encoded
+/// [`BcsErrorBody`] come back as [`BcsClientError::Server`].
+#[derive(Debug, Error)]
+pub enum BcsClientError {
+  #[error("HTTP error: {}",.0)]
+  Http(#[from] reqwest::Error),
+  #[error("Server rejected the request ({}): {}",.0.code,.0.detail)]
+  Server(BcsErrorBody),
+  #[error(transparent)]
+  Rejection(#[from] BcsRejection),
+}
+
+impl From<bcs::Error> for BcsClientError {
+  fn from(err: bcs::Error) -> Self {
+    Self::Rejection(err.into())
+  }
+}
+
+/// Extension methods for sending BCS request bodies.
+pub trait BcsRequestBuilderExt: Sized {
+  /// Encodes `value` as the request body and sets `Content-Type: application/x-bcs`.
+  fn bcs<T>(self, value: &T) -> Result<Self, BcsClientError>
+  where
+    T: Serialize + ?Sized;
+}
+
+impl BcsRequestBuilderExt for reqwest::RequestBuilder {
+  fn bcs<T>(self, value: &T) -> Result<Self, BcsClientError>
+  where
+    T: Serialize + ?Sized,
+  {
+    let buf = bcs::to_bytes(value)?;
+    Ok(
+      self
+        .header(header::CONTENT_TYPE, HeaderValue::from_static(APPLICATION_BCS))
+        .body(buf),
+    )
+  }
+}
+
+/// Extension methods for decoding BCS response bodies.
+pub trait BcsResponseExt {
+  /// Checks the status and `Content-Type`, then decodes the body.
+  ///
+  /// Error responses with a BCS body are decoded as a [`BcsErrorBody`] and returned as
+  /// [`BcsClientError::Server`].
+  fn bcs<T>(self) -> impl Future<Output = Result<T, BcsClientError>> + Send
+  where
+    T: DeserializeOwned;
+}
+
+impl BcsResponseExt for reqwest::Response {
+  async fn bcs<T>(self) -> Result<T, BcsClientError>
+  where
+    T: DeserializeOwned,
+  {
+    let config = BcsConfig::default();
+    let is_bcs = bcs_content_type(self.headers(), config.content_type_check);
+    if let Err(err) = self.error_for_status_ref() {
+      if is_bcs
+        && let Ok(bytes) = self.bytes().await
+        && let Ok(body) = decode::<BcsErrorBody>(&bytes, &config)
+      {
+        return Err(BcsClientError::Server(body));
+      }
+      return Err(err.into());
+    }
+    if !is_bcs {
+      return Err(BcsRejection::MissingContentType.into());
+    }
+
+    let bytes = self.bytes().await?;
+    Ok(decode(&bytes, &config)?)
+  }
+}
@@ -0,0 +1,198 @@
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "brotli"))]
+use std::io::{self, Read};
+
+use axum_core::response::{IntoResponse, Response};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, header};
+
+use crate::BcsRejection;
+
+/// A `Content-Encoding` the crate can decode and produce.
+///
+/// Each codec sits behind the cargo feature of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+  #[cfg(feature = "zstd")]
+  Zstd,
+  #[cfg(feature = "brotli")]
+  Brotli,
+  #[cfg(feature = "gzip")]
+  Gzip,
+}
+
+impl Encoding {
+  /// Codecs in server preference order, used to break ties between equal qualities.
+  const ALL: &[Encoding] = &[
+    #[cfg(feature = "zstd")]
+    Encoding::Zstd,
+    #[cfg(feature = "brotli")]
+    Encoding::Brotli,
+    #[cfg(feature = "gzip")]
+    Encoding::Gzip,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      #[cfg(feature = "zstd")]
+      Encoding::Zstd => "zstd",
+      #[cfg(feature = "brotli")]
+      Encoding::Brotli => "br",
+      #[cfg(feature = "gzip")]
+      Encoding::Gzip => "gzip",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    let name = if name.eq_ignore_ascii_case("x-gzip") { "gzip" } else { name };
+    Self::ALL
+      .iter()
+      .copied()
+      .find(|encoding| encoding.as_str().eq_ignore_ascii_case(name))
+  }
+}
+
+/// Whether a request `Content-Encoding` leaves the body as is.
+pub(crate) fn is_identity(content_encoding: Option<&HeaderValue>) -> bool {
+  content_encoding.is_none_or(|value| value.as_bytes().trim_ascii().eq_ignore_ascii_case(b"identity"))
+}
+
+/// Decodes `bytes` according to the request `Content-Encoding`, reading at most `limit`
+/// decoded bytes.
+pub(crate) fn decompress(
+  content_encoding: Option<&HeaderValue>,
+  bytes: Bytes,
+  limit: usize,
+) -> Result<Bytes, BcsRejection> {
+  if is_identity(content_encoding) {
+    return Ok(bytes);
+  }
+
+  let encoding = content_encoding
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| Encoding::from_name(value.trim()))
+    .ok_or(BcsRejection::UnsupportedEncoding)?;
+  decompress_with(encoding, &bytes, limit)
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "brotli"))]
+fn decompress_with(encoding: Encoding, bytes: &[u8], limit: usize) -> Result<Bytes, BcsRejection> {
+  let reader: Box<dyn Read + '_> = match encoding {
+    #[cfg(feature = "zstd")]
+    Encoding::Zstd => Box::new(zstd::stream::read::Decoder::new(bytes).map_err(BcsRejection::Decompression)?),
+    #[cfg(feature = "brotli")]
+    Encoding::Brotli => Box::new(brotli::Decompressor::new(bytes, 4096)),
+    #[cfg(feature = "gzip")]
+    Encoding::Gzip => Box::new(flate2::read::GzDecoder::new(bytes)),
+  };
+
+  let mut buf = Vec::new();
+  reader
+    .take(limit as u64 + 1)
+    .read_to_end(&mut buf)
+    .map_err(BcsRejection::Decompression)?;
+  if buf.len() > limit {
+    return Err(BcsRejection::DecompressedTooLarge { limit });
+  }
+  Ok(Bytes::from(buf))
+}
+
+#[cfg(not(any(feature = "gzip", feature = "zstd", feature = "brotli")))]
+fn decompress_with(encoding: Encoding, _bytes: &[u8], _limit: usize) -> Result<Bytes, BcsRejection> {
+  match encoding {}
+}
+
+/// Picks the preferred codec the request's `Accept-Encoding` allows.
+pub(crate) fn accept_encoding(headers: &HeaderMap) -> Option<Encoding> {
+  let codings: Vec<(&str, u16)> = headers
+    .get_all(header::ACCEPT_ENCODING)
+    .iter()
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .filter_map(|coding| {
+      let mut params = coding.split(';');
+      let name = params.next()?.trim();
+      let quality = params
+        .filter_map(|param| param.trim().strip_prefix("q="))
+        .find_map(|q| q.parse::<f32>().ok())
+        .map_or(1000, |q| (q.clamp(0.0, 1.0) * 1000.0) as u16);
+      (!name.is_empty()).then_some((name, quality))
+    })
+    .collect();
+
+  let mut best: Option<(Encoding, u16)> = None;
+  for &encoding in Encoding::ALL {
+    let quality = codings
+      .iter()
+      .find(|(name, _)| Encoding::from_name(name) == Some(encoding))
+      .or_else(|| codings.iter().find(|(name, _)| *name == "*"))
+      .map(|&(_, quality)| quality);
+    if let Some(quality) = quality
+      && quality > 0
+      && best.is_none_or(|(_, q)| quality > q)
+    {
+      best = Some((encoding, quality));
+    }
+  }
+  best.map(|(encoding, _)| encoding)
+}
+
+/// Builds a response from an encoded body, compressing it with `encoding` when it is at
+/// least `threshold` bytes long.
+///
+/// With a codec feature enabled, the response always carries `Vary: Accept-Encoding`, since
+/// whether it is compressed depends on that header.
+pub(crate) fn compressed_response(
+  content_type: HeaderValue,
+  buf: Vec<u8>,
+  encoding: Option<Encoding>,
+  threshold: usize,
+) -> Response {
+  #[cfg(any(feature = "gzip", feature = "zstd", feature = "brotli"))]
+  {
+    let vary = (header::VARY, HeaderValue::from_static("accept-encoding"));
+    if let Some(encoding) = encoding
+      && buf.len() >= threshold
+      && let Ok(compressed) = compress(encoding, &buf)
+    {
+      return (
+        [
+          (header::CONTENT_TYPE, content_type),
+          (header::CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str())),
+          vary,
+        ],
+        Bytes::from(compressed),
+      ).into_response();
+    }
+    ([(header::CONTENT_TYPE, content_type), vary], Bytes::from(buf)).into_response()
+  }
+
+  #[cfg(not(any(feature = "gzip", feature = "zstd", feature = "brotli")))]
+  {
+    let _ = (encoding, threshold);
+    ([(header::CONTENT_TYPE, content_type)], Bytes::from(buf)).into_response()
+  }
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "brotli"))]
+fn compress(encoding: Encoding, bytes: &[u8]) -> io::Result<Vec<u8>> {
+  #[cfg(any(feature = "gzip", feature = "brotli"))]
+  use std::io::Write;
+
+  match encoding {
+    #[cfg(feature = "zstd")]
+    Encoding::Zstd => zstd::bulk::compress(bytes, zstd::DEFAULT_COMPRESSION_LEVEL),
+    #[cfg(feature = "brotli")]
+    Encoding::Brotli => {
+      let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+      writer.write_all(bytes)?;
+      Ok(writer.into_inner())
+    }
+    #[cfg(feature = "gzip")]
+    Encoding::Gzip => {
+      let mut writer = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+      writer.write_all(bytes)?;
+      writer.finish()
+    }
+  }
+}
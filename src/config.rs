@@ -0,0 +1,144 @@
+use bytes::Bytes;
+
+use crate::SchemaVersionSource;
+
+const DEFAULT_DECOMPRESSION_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Per-route decoding options for the BCS extractors.
+///
+/// The extractors look the config up in the request extensions, so it can be
+/// attached to a whole router or a single route with an `Extension` layer.
+/// Requests without a config use [`BcsConfig::default`].
+#[derive(Debug, Clone)]
+pub struct BcsConfig {
+  pub(crate) max_container_depth: usize,
+  pub(crate) body_limit: Option<usize>,
+  pub(crate) frame_limit: Option<usize>,
+  pub(crate) content_type_check: ContentTypeCheck,
+  pub(crate) digest_domain: Bytes,
+  pub(crate) require_digest: bool,
+  pub(crate) signature_domain: Bytes,
+  pub(crate) decompression_limit: Option<usize>,
+  pub(crate) compression_threshold: usize,
+  pub(crate) schema_version_source: SchemaVersionSource,
+  pub(crate) text_bodies: bool,
+}
+
+/// How strictly the extractors match the request `Content-Type`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentTypeCheck {
+  /// Only `application/x-bcs` and `+bcs` suffixed types.
+  Strict,
+  /// BCS types plus `application/octet-stream` and `+octet-stream` suffixed types.
+  #[default]
+  Lenient,
+  /// Any content type, including none at all.
+  Disabled,
+}
+
+impl BcsConfig {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Caps the nesting depth accepted while decoding.
+  ///
+  /// Values above [`bcs::MAX_CONTAINER_DEPTH`] are clamped to it.
+  pub fn max_container_depth(mut self, limit: usize) -> Self {
+    self.max_container_depth = limit.min(bcs::MAX_CONTAINER_DEPTH);
+    self
+  }
+
+  /// Rejects bodies larger than `limit` bytes with `413 Payload Too Large`.
+  ///
+  /// This applies on top of axum's `DefaultBodyLimit`; the smaller of the two wins, and only
+  /// this limit is reported as [`BcsRejection::PayloadTooLarge`](crate::BcsRejection::PayloadTooLarge).
+  pub fn body_limit(mut self, limit: usize) -> Self {
+    self.body_limit = Some(limit);
+    self
+  }
+
+  /// Rejects frames longer than `limit` bytes in [`BcsFrames`](crate::BcsFrames).
+  pub fn frame_limit(mut self, limit: usize) -> Self {
+    self.frame_limit = Some(limit);
+    self
+  }
+
+  pub fn content_type_check(mut self, check: ContentTypeCheck) -> Self {
+    self.content_type_check = check;
+    self
+  }
+
+  /// Domain-separation prefix hashed ahead of the body by `BcsHashed`.
+  pub fn digest_domain(mut self, domain: impl Into<Bytes>) -> Self {
+    self.digest_domain = domain.into();
+    self
+  }
+
+  /// Rejects `BcsHashed` requests that carry no `x-content-digest` header.
+  pub fn require_digest(mut self, require: bool) -> Self {
+    self.require_digest = require;
+    self
+  }
+
+  /// Domain-separation prefix covered by Ed25519 signatures ahead of the payload.
+  pub fn signature_domain(mut self, domain: impl Into<Bytes>) -> Self {
+    self.signature_domain = domain.into();
+    self
+  }
+
+  /// Caps the size of a decompressed request body.
+  ///
+  /// Defaults to the body limit, or to 2 MiB if none is set.
+  pub fn decompression_limit(mut self, limit: usize) -> Self {
+    self.decompression_limit = Some(limit);
+    self
+  }
+
+  /// Smallest response body [`Negotiated`](crate::Negotiated) compresses. Defaults to 1 KiB.
+  pub fn compression_threshold(mut self, threshold: usize) -> Self {
+    self.compression_threshold = threshold;
+    self
+  }
+
+  /// Where [`VersionedBcs`](crate::VersionedBcs) reads the request's schema version from.
+  pub fn schema_version_source(mut self, source: SchemaVersionSource) -> Self {
+    self.schema_version_source = source;
+    self
+  }
+
+  /// Accepts request bodies of hex or base64 text instead of raw BCS.
+  ///
+  /// A body is read as text when its `Content-Type` has a `+hex`, `+base64` or `+base64url`
+  /// suffix, or when a `Content-Transfer-Encoding` header names one of those encodings.
+  /// Needs the `text` feature.
+  pub fn text_bodies(mut self, enabled: bool) -> Self {
+    self.text_bodies = enabled;
+    self
+  }
+
+  pub(crate) fn effective_decompression_limit(&self) -> usize {
+    self
+      .decompression_limit
+      .or(self.body_limit)
+      .unwrap_or(DEFAULT_DECOMPRESSION_LIMIT)
+  }
+}
+
+impl Default for BcsConfig {
+  fn default() -> Self {
+    Self {
+      max_container_depth: bcs::MAX_CONTAINER_DEPTH,
+      body_limit: None,
+      frame_limit: None,
+      content_type_check: ContentTypeCheck::default(),
+      digest_domain: Bytes::new(),
+      require_digest: false,
+      signature_domain: Bytes::new(),
+      decompression_limit: None,
+      compression_threshold: 1024,
+      schema_version_source: SchemaVersionSource::default(),
+      text_bodies: false,
+    }
+  }
+}
@@ -0,0 +1,134 @@
+use axum::{Router, routing::get};
+use axum_core::response::IntoResponse;
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, header};
+use serde_reflection::{Registry, Tracer, TracerConfig};
+
+use crate::{
+  BcsReflect,
+  negotiate::{accept_ranges, match_quality},
+};
+
+/// Path [`BcsSchemaExport::into_router`] serves the registry at.
+pub const BCS_SCHEMA_PATH: &str = "/.well-known/bcs-schema";
+
+const APPLICATION_YAML: &str = "application/yaml";
+
+/// Traces a set of request and response types into one `serde-reflection` registry and
+/// serves it for client code generators such as `serde-generate`.
+///
+/// The registry is sent as JSON, or as YAML when the `Accept` header prefers
+/// `application/yaml`.
+pub struct BcsSchemaExport {
+  tracer: Tracer,
+}
+
+impl BcsSchemaExport {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds `T` and the types it contains to the registry.
+  pub fn trace<T>(mut self) -> Result<Self, serde_reflection::Error>
+  where
+    T: BcsReflect,
+  {
+    T::trace_nested(&mut self.tracer)?;
+    self.tracer.trace_simple_type::<T>()?;
+    Ok(self)
+  }
+
+  /// Finishes tracing and returns the combined registry.
+  pub fn registry(self) -> Result<Registry, serde_reflection::Error> {
+    self.tracer.registry()
+  }
+
+  /// Finishes tracing and returns a router serving the registry at [`BCS_SCHEMA_PATH`].
+  pub fn into_router<S>(self) -> Result<Router<S>, serde_reflection::Error>
+  where
+    S: Clone + Send + Sync + 'static,
+  {
+    let registry = self.registry()?;
+    let value = serde_json::to_value(&registry)
+      .map_err(|err| serde_reflection::Error::Custom(err.to_string()))?;
+    let json = serde_json::to_vec(&value)
+      .map_err(|err| serde_reflection::Error::Custom(err.to_string()))?;
+    let yaml = serde_yaml::to_string(&yaml_value(value, false))
+      .map_err(|err| serde_reflection::Error::Custom(err.to_string()))?;
+    let (json, yaml) = (Bytes::from(json), Bytes::from(yaml));
+
+    Ok(Router::new().route(
+      BCS_SCHEMA_PATH,
+      get(move |headers: HeaderMap| async move {
+        if prefers_yaml(&headers) {
+          (
+            [(header::CONTENT_TYPE, HeaderValue::from_static(APPLICATION_YAML))],
+            yaml,
+          ).into_response()
+        } else {
+          (
+            [(
+              header::CONTENT_TYPE,
+              HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+            )],
+            json,
+          ).into_response()
+        }
+      }),
+    ))
+  }
+}
+
+impl Default for BcsSchemaExport {
+  fn default() -> Self {
+    Self {
+      tracer: Tracer::new(TracerConfig::default()),
+    }
+  }
+}
+
+fn prefers_yaml(headers: &HeaderMap) -> bool {
+  let ranges = accept_ranges(headers);
+  let yaml = ["yaml", "x-yaml"]
+    .into_iter()
+    .filter_map(|subtype| match_quality(&ranges, "application", subtype))
+    .chain(match_quality(&ranges, "text", "yaml"))
+    .max_by_key(|&(quality, specificity)| (specificity, quality));
+  let json = match_quality(&ranges, "application", "json");
+  yaml.is_some_and(|(quality, specificity)| {
+    quality > 0 && json.is_none_or(|(q, s)| (quality, specificity) > (q, s))
+  })
+}
+
+/// Converts the JSON form of a registry to YAML in the layout `serde-generate` reads.
+///
+/// `serde_yaml` writes nested enums as YAML tags, so the registry goes through JSON, where
+/// they are single-key maps. JSON keys are always strings; variant indices under `ENUM`
+/// are turned back into integers.
+fn yaml_value(value: serde_json::Value, variants: bool) -> serde_yaml::Value {
+  match value {
+    serde_json::Value::Null => serde_yaml::Value::Null,
+    serde_json::Value::Bool(value) => serde_yaml::Value::Bool(value),
+    serde_json::Value::Number(number) => match number.as_u64() {
+      Some(number) => serde_yaml::Value::Number(number.into()),
+      None => serde_yaml::Value::String(number.to_string()),
+    },
+    serde_json::Value::String(value) => serde_yaml::Value::String(value),
+    serde_json::Value::Array(items) => {
+      serde_yaml::Value::Sequence(items.into_iter().map(|item| yaml_value(item, false)).collect())
+    }
+    serde_json::Value::Object(map) => serde_yaml::Value::Mapping(
+      map
+        .into_iter()
+        .map(|(key, value)| {
+          let value = yaml_value(value, key == "ENUM");
+          let key = match key.parse::<u32>() {
+            Ok(index) if variants => serde_yaml::Value::Number(index.into()),
+            _ => serde_yaml::Value::String(key),
+          };
+          (key, value)
+        })
+        .collect(),
+    ),
+  }
+}
@@ -0,0 +1,60 @@
+use axum_core::extract::{FromRequest, Request};
+use digest::{Digest, Output, OutputSizeUser};
+use http::HeaderName;
+use serde::de::DeserializeOwned;
+
+use crate::{BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// Header carrying the hex-encoded digest of a request body.
+pub const X_CONTENT_DIGEST: HeaderName = HeaderName::from_static("x-content-digest");
+
+/// Extractor that decodes like [`Bcs`](crate::Bcs) and hashes the BCS bytes with `H`.
+///
+/// The digest covers the route's `digest_domain` followed by the BCS payload, after any
+/// `Content-Encoding` or text transport has been undone, so it does not change with how
+/// the body was sent. When the request has
+/// an [`X_CONTENT_DIGEST`] header, the digest must match it; [`BcsConfig::require_digest`]
+/// makes the header mandatory.
+///
+/// [`BcsConfig::require_digest`]: crate::BcsConfig::require_digest
+pub struct BcsHashed<T, H: OutputSizeUser>(pub T, pub Output<H>);
+
+impl<S, T, H> FromRequest<S> for BcsHashed<T, H>
+where
+  T: DeserializeOwned,
+  H: Digest,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if !bcs_body_type(req.headers(), &config) {
+      return Err(BcsRejection::MissingContentType);
+    }
+
+    let expected = req.headers().get(X_CONTENT_DIGEST).cloned();
+    if expected.is_none() && config.require_digest {
+      return Err(BcsRejection::MissingDigest);
+    }
+
+    let bytes = read_body(req, _s, &config).await?;
+    let digest = H::new()
+      .chain_update(&config.digest_domain)
+      .chain_update(&bytes)
+      .finalize();
+
+    if let Some(expected) = expected {
+      let expected = expected.to_str().ok().map(|value| value.strip_prefix("0x").unwrap_or(value));
+      let matches = expected
+        .and_then(|value| hex::decode(value).ok())
+        .is_some_and(|expected| expected == digest.as_slice());
+      if !matches {
+        return Err(BcsRejection::DigestMismatch);
+      }
+    }
+
+    let value = decode(&bytes, &config)?;
+    Ok(BcsHashed(value, digest))
+  }
+}
@@ -1,36 +1,244 @@
 use std::ops::{Deref, DerefMut};
 
 use axum_core::{
-  extract::{FromRequest, Request},
+  BoxError,
+  body::Body,
+  extract::{
+    FromRequest, Request,
+    rejection::{BytesRejection, FailedToBufferBody},
+  },
   response::{IntoResponse, Response},
 };
 use bytes::Bytes;
-use http::{HeaderValue, StatusCode, header};
-use serde::{Serialize, de::DeserializeOwned};
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
 
+mod borrowed;
+mod canonical;
+mod compression;
+#[cfg(feature = "reqwest")]
+mod client;
+mod config;
+#[cfg(feature = "schema-export")]
+mod export;
+#[cfg(feature = "hash")]
+mod hashed;
+mod negotiate;
+#[cfg(feature = "text")]
+mod params;
+#[cfg(any(feature = "aide", feature = "utoipa"))]
+mod openapi;
+mod prefix;
+mod problem;
+#[cfg(feature = "reflection")]
+mod reflection;
+#[cfg(feature = "ed25519")]
+mod signed;
+mod stream;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod text;
+#[cfg(feature = "text")]
+mod typed_header;
+mod validated;
+mod versioned;
+mod with_raw;
+
+pub use borrowed::BcsBytes;
+pub use canonical::CanonicalBcs;
+pub use compression::Encoding;
+#[cfg(feature = "reqwest")]
+pub use client::{BcsClientError, BcsRequestBuilderExt, BcsResponseExt};
+pub use config::{BcsConfig, ContentTypeCheck};
+#[cfg(feature = "schema-export")]
+pub use export::{BCS_SCHEMA_PATH, BcsSchemaExport};
+#[cfg(feature = "hash")]
+pub use hashed::{BcsHashed, X_CONTENT_DIGEST};
+#[cfg(feature = "blake2")]
+pub use blake2;
+#[cfg(feature = "sha2")]
+pub use sha2;
+#[cfg(feature = "sha3")]
+pub use sha3;
+#[cfg(feature = "json")]
+pub use negotiate::BcsOrJson;
+pub use negotiate::{Format, Negotiate, Negotiated};
+#[cfg(feature = "path")]
+pub use params::BcsPath;
+#[cfg(feature = "text")]
+pub use params::{BcsParamName, BcsQuery, DefaultBcsParam};
+pub use prefix::BcsPrefix;
+#[cfg(feature = "json")]
+pub use problem::ProblemJson;
+#[cfg(feature = "reflection")]
+pub use reflection::{BcsReflect, BcsSchema, FingerprintedBcs, X_BCS_SCHEMA_FINGERPRINT};
+#[cfg(feature = "reflection")]
+pub use serde_reflection;
+pub use problem::{BcsDecodeErrorKind, BcsErrorBody, BcsErrorKind, BcsErrorResponse};
+#[cfg(feature = "ed25519")]
+pub use signed::{
+  BcsSigner, BcsSigningKey, SignedBcs, SignedBcsResponse, SignedEnvelope, X_BCS_PUBLIC_KEY, X_BCS_SIGNATURE,
+};
+pub use stream::{BcsFrames, BcsStream};
+pub use text::TextEncoding;
+#[cfg(feature = "text")]
+pub use typed_header::{BcsHeader, BcsHeaderName};
+pub use validated::{BcsFieldError, BcsValidate, ValidatedBcs};
+#[cfg(feature = "validator")]
+pub use validated::ValidBcs;
+#[cfg(feature = "validator")]
+pub use validator;
+pub use versioned::{BcsMigrate, SchemaVersionSource, VersionedBcs, VersionedResponse, X_BCS_SCHEMA_VERSION};
+pub use with_raw::BcsWithRaw;
+#[cfg(feature = "ed25519")]
+pub use ed25519_dalek;
+
+/// The media type emitted for BCS bodies.
+pub const APPLICATION_BCS: &str = "application/x-bcs";
 
 pub struct Bcs<T>(pub T);
 
+/// Rejection returned by the BCS extractors.
+///
+/// Some variants only exist with the cargo feature that produces them, so matches need a
+/// wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum BcsRejection {
   #[error("Bytes read error: {}",.0)]
-  BytesRead(#[from] axum_core::extract::rejection::BytesRejection),
-  #[error("Missing octet-stream content type")]
+  BytesRead(#[from] BytesRejection),
+  #[error("Missing BCS content type")]
   MissingContentType,
-  #[error("BCS parse error: {}",.0)]
-  BcsError(#[from] bcs::Error),
+  #[error("BCS parse error: {}",.source)]
+  BcsError {
+    source: bcs::Error,
+    offset: Option<usize>,
+  },
+  #[error("BCS container depth limit of {} exceeded",.limit)]
+  ContainerDepthExceeded { limit: usize },
+  #[error("Payload too large: limit is {} bytes",.limit)]
+  PayloadTooLarge { limit: usize },
+  #[error("Trailing bytes: {} bytes left after decoding {} bytes",.remaining,.consumed)]
+  TrailingBytes { consumed: usize, remaining: usize },
+  #[error("Frame too large: limit is {} bytes",.limit)]
+  FrameTooLarge { limit: usize },
+  #[error("Failed to read the request body: {}",.0)]
+  StreamRead(axum_core::Error),
+  #[cfg(feature = "json")]
+  #[error("JSON parse error: {}",.0)]
+  JsonError(#[from] serde_json::Error),
+  #[error("Missing content digest header")]
+  MissingDigest,
+  #[error("Content digest does not match the request body")]
+  DigestMismatch,
+  #[error("Missing or invalid signature")]
+  InvalidSignature,
+  #[error("Unsupported content encoding")]
+  UnsupportedEncoding,
+  #[error("Failed to decompress the request body: {}",.0)]
+  Decompression(std::io::Error),
+  #[error("Decompressed body too large: limit is {} bytes",.limit)]
+  DecompressedTooLarge { limit: usize },
+  #[error("Missing or malformed schema version")]
+  InvalidSchemaVersion,
+  #[error("Unsupported schema version {}",.version)]
+  UnsupportedSchemaVersion { version: u32 },
+  #[cfg(feature = "reflection")]
+  #[error("Schema fingerprint mismatch: expected {}",.expected)]
+  SchemaMismatch { expected: String },
+  #[cfg(feature = "reflection")]
+  #[error("Failed to trace the BCS schema: {}",.0)]
+  SchemaTrace(String),
+  #[error("Validation failed: {}",validated::format_field_errors(.0))]
+  Validation(Vec<BcsFieldError>),
+  #[error("Non-canonical BCS encoding: differs from the re-encoded value at byte {}",.offset)]
+  NonCanonical { offset: usize },
+  #[error("Missing parameter `{}`",.name)]
+  MissingParam { name: String },
+  #[error("Invalid hex or base64 encoding")]
+  InvalidTextEncoding,
+  #[error("Missing header `{}`",.name)]
+  MissingHeader { name: String },
+}
+
+impl From<bcs::Error> for BcsRejection {
+  fn from(source: bcs::Error) -> Self {
+    Self::BcsError { source, offset: None }
+  }
+}
+
+impl BcsRejection {
+  /// Every status code a rejection can respond with, for API documentation.
+  #[cfg(any(feature = "aide", feature = "utoipa"))]
+  pub(crate) const STATUS_CODES: &[StatusCode] = &[
+    StatusCode::BAD_REQUEST,
+    #[cfg(feature = "ed25519")]
+    StatusCode::UNAUTHORIZED,
+    #[cfg(feature = "reflection")]
+    StatusCode::CONFLICT,
+    StatusCode::PAYLOAD_TOO_LARGE,
+    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+    StatusCode::UNPROCESSABLE_ENTITY,
+    #[cfg(feature = "reflection")]
+    StatusCode::INTERNAL_SERVER_ERROR,
+  ];
+
+  /// Get the response body text used for this rejection.
+  pub fn body_text(&self) -> String {
+    match self {
+      Self::BytesRead(inner) => inner.body_text(),
+      _ => self.to_string(),
+    }
+  }
+
+  /// Get the status code used for this rejection.
+  pub fn status(&self) -> StatusCode {
+    match self {
+      Self::BytesRead(inner) => inner.status(),
+      Self::MissingContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+      Self::BcsError { .. } => StatusCode::BAD_REQUEST,
+      Self::ContainerDepthExceeded { .. } => StatusCode::BAD_REQUEST,
+      Self::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+      Self::TrailingBytes { .. } => StatusCode::BAD_REQUEST,
+      Self::FrameTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+      Self::StreamRead(err) if caused_by::<http_body_util::LengthLimitError>(err) => StatusCode::PAYLOAD_TOO_LARGE,
+      Self::StreamRead(_) => StatusCode::BAD_REQUEST,
+      #[cfg(feature = "json")]
+      Self::JsonError(err) if err.is_data() => StatusCode::UNPROCESSABLE_ENTITY,
+      #[cfg(feature = "json")]
+      Self::JsonError(_) => StatusCode::BAD_REQUEST,
+      Self::MissingDigest => StatusCode::BAD_REQUEST,
+      Self::DigestMismatch => StatusCode::BAD_REQUEST,
+      Self::InvalidSignature => StatusCode::UNAUTHORIZED,
+      Self::UnsupportedEncoding => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+      Self::Decompression(_) => StatusCode::BAD_REQUEST,
+      Self::DecompressedTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+      Self::InvalidSchemaVersion => StatusCode::BAD_REQUEST,
+      Self::UnsupportedSchemaVersion { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+      #[cfg(feature = "reflection")]
+      Self::SchemaMismatch { .. } => StatusCode::CONFLICT,
+      #[cfg(feature = "reflection")]
+      Self::SchemaTrace(_) => StatusCode::INTERNAL_SERVER_ERROR,
+      Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+      Self::NonCanonical { .. } => StatusCode::BAD_REQUEST,
+      Self::MissingParam { .. } => StatusCode::BAD_REQUEST,
+      Self::InvalidTextEncoding => StatusCode::BAD_REQUEST,
+      Self::MissingHeader { .. } => StatusCode::BAD_REQUEST,
+    }
+  }
 }
 
 impl IntoResponse for BcsRejection {
   fn into_response(self) -> axum_core::response::Response {
     (
-      StatusCode::BAD_REQUEST,
+      self.status(),
       [(
         header::CONTENT_TYPE,
         HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
       )],
-      self.to_string(),
+      self.body_text(),
     ).into_response()
   }
 }
@@ -43,20 +251,184 @@ where
   type Rejection = BcsRejection;
 
   async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
-    if bcs_content_type(&req) {
-      let bytes = Bytes::from_request(req, _s).await?;
-      match bcs::from_bytes(&bytes) {
-        Ok(value) => Ok(Bcs(value)),
-        Err(err) => Err(err.into()),
-      }
+    let config = bcs_config(&req);
+    if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      decode(&bytes, &config).map(Bcs)
     } else {
       Err(BcsRejection::MissingContentType)
     }
   }
 }
 
-fn bcs_content_type<B>(req: &Request<B>) -> bool {
-  let content_type = if let Some(content_type) = req.headers().get(header::CONTENT_TYPE) {
+fn bcs_config<B>(req: &Request<B>) -> BcsConfig {
+  req.extensions().get::<BcsConfig>().cloned().unwrap_or_default()
+}
+
+async fn read_body<S>(req: Request, state: &S, config: &BcsConfig) -> Result<Bytes, BcsRejection>
+where
+  S: Send + Sync,
+{
+  let content_encoding = req.headers().get(header::CONTENT_ENCODING).cloned();
+  let transport = text::text_transport(req.headers(), config);
+  let bytes = read_raw_body(req, state, config).await?;
+  let bytes = compression::decompress(content_encoding.as_ref(), bytes, config.effective_decompression_limit())?;
+  match transport {
+    #[cfg(feature = "text")]
+    Some(encoding) => encoding.decode(bytes.trim_ascii()).map(Bytes::from),
+    _ => Ok(bytes),
+  }
+}
+
+async fn read_raw_body<S>(req: Request, state: &S, config: &BcsConfig) -> Result<Bytes, BcsRejection>
+where
+  S: Send + Sync,
+{
+  let Some(limit) = config.body_limit else {
+    return Ok(Bytes::from_request(req, state).await?);
+  };
+
+  let content_length = req
+    .headers()
+    .get(header::CONTENT_LENGTH)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse::<u64>().ok());
+  if content_length.is_some_and(|len| len > limit as u64) {
+    return Err(BcsRejection::PayloadTooLarge { limit });
+  }
+
+  // axum's own `DefaultBodyLimit` still applies and is reported as its own rejection.
+  let req = req.map(|body| limit_body(body, limit));
+  match Bytes::from_request(req, state).await {
+    Ok(bytes) => Ok(bytes),
+    Err(BytesRejection::FailedToBufferBody(FailedToBufferBody::UnknownBodyError(err)))
+      if exceeded_body_limit(&err) =>
+    {
+      Err(BcsRejection::PayloadTooLarge { limit })
+    }
+    Err(err) => Err(err.into()),
+  }
+}
+
+/// Error reported by a body wrapped with [`limit_body`], so that [`BcsConfig::body_limit`]
+/// can be told apart from axum's `DefaultBodyLimit`.
+#[derive(Debug, Error)]
+#[error("request body exceeded the configured BCS body limit")]
+struct BodyLimitExceeded;
+
+/// Fails reading `body` with [`BodyLimitExceeded`] once it goes past `limit` bytes.
+pub(crate) fn limit_body(body: Body, limit: usize) -> Body {
+  Body::new(http_body_util::Limited::new(body, limit).map_err(|err| {
+    if err.is::<http_body_util::LengthLimitError>() {
+      BoxError::from(BodyLimitExceeded)
+    } else {
+      err
+    }
+  }))
+}
+
+/// Whether `err` was caused by a body wrapped with [`limit_body`] going over its limit.
+pub(crate) fn exceeded_body_limit(err: &(dyn std::error::Error + 'static)) -> bool {
+  caused_by::<BodyLimitExceeded>(err)
+}
+
+/// Whether `err` or any of its sources is an `E`.
+fn caused_by<E>(err: &(dyn std::error::Error + 'static)) -> bool
+where
+  E: std::error::Error + 'static,
+{
+  let mut source = Some(err);
+  while let Some(inner) = source {
+    if inner.is::<E>() {
+      return true;
+    }
+    source = inner.source();
+  }
+  false
+}
+
+fn decode<'a, T>(bytes: &'a [u8], config: &BcsConfig) -> Result<T, BcsRejection>
+where
+  T: Deserialize<'a>,
+{
+  match bcs::from_bytes_with_limit(bytes, config.max_container_depth) {
+    Ok(value) => Ok(value),
+    Err(bcs::Error::ExceededContainerDepthLimit(_)) => Err(BcsRejection::ContainerDepthExceeded {
+      limit: config.max_container_depth,
+    }),
+    Err(bcs::Error::RemainingInput) => {
+      let consumed = decoded_len::<T>(bytes, config.max_container_depth);
+      Err(BcsRejection::TrailingBytes {
+        consumed,
+        remaining: bytes.len() - consumed,
+      })
+    }
+    // Every prefix of the input runs out too, so the failure is at its end.
+    Err(bcs::Error::Eof) => Err(BcsRejection::BcsError {
+      offset: Some(bytes.len()),
+      source: bcs::Error::Eof,
+    }),
+    Err(source) => {
+      // The decoded prefix ends with the offending byte.
+      let offset = decoded_len::<T>(bytes, config.max_container_depth).saturating_sub(1);
+      Err(BcsRejection::BcsError {
+        offset: Some(offset),
+        source,
+      })
+    }
+  }
+}
+
+/// Decodes the leading value of `bytes`, returning it with the number of bytes it spans.
+fn decode_prefix<T>(bytes: &[u8], config: &BcsConfig) -> Result<(T, usize), BcsRejection>
+where
+  T: DeserializeOwned,
+{
+  match decode(bytes, config) {
+    Ok(value) => Ok((value, bytes.len())),
+    Err(BcsRejection::TrailingBytes { consumed, .. }) => {
+      decode(&bytes[..consumed], config).map(|value| (value, consumed))
+    }
+    Err(err) => Err(err),
+  }
+}
+
+/// Returns how many leading bytes the decoder reads before it stops asking for more input.
+///
+/// Only called once decoding has failed, since it decodes up to `log2(len)` prefixes. Each
+/// of them goes through the slice decoder, which checks a declared length against the
+/// remaining input before allocating for it.
+///
+/// Decoding a prefix behaves exactly like decoding the whole input until the prefix runs
+/// out, so the shortest prefix that does not fail with `Eof` ends where decoding succeeded
+/// or where it failed.
+fn decoded_len<'a, T>(bytes: &'a [u8], limit: usize) -> usize
+where
+  T: Deserialize<'a>,
+{
+  let (mut low, mut high) = (0, bytes.len());
+  while low < high {
+    let mid = low + (high - low) / 2;
+    match bcs::from_bytes_with_limit::<T>(&bytes[..mid], limit) {
+      Err(bcs::Error::Eof) => low = mid + 1,
+      _ => high = mid,
+    }
+  }
+  low
+}
+
+/// Whether a request carries a BCS body, either as bytes or, when enabled, as hex or base64
+/// text.
+fn bcs_body_type(headers: &HeaderMap, config: &BcsConfig) -> bool {
+  bcs_content_type(headers, config.content_type_check) || text::text_transport(headers, config).is_some()
+}
+
+fn bcs_content_type(headers: &HeaderMap, check: ContentTypeCheck) -> bool {
+  if check == ContentTypeCheck::Disabled {
+    return true;
+  }
+
+  let content_type = if let Some(content_type) = headers.get(header::CONTENT_TYPE) {
     content_type
   } else {
     return false;
@@ -74,11 +446,19 @@ fn bcs_content_type<B>(req: &Request<B>) -> bool {
     return false;
   };
 
-  let is_binary_content_type = mime.type_() == "application"
-    && (mime.subtype() == "octet-stream"
-      || mime.suffix().map_or(false, |name| name == "octet-stream"));
+  // `subtype()` drops the suffix, so compare the whole essence: `application/x-bcs+hex` is
+  // a text transport, not raw BCS.
+  let is_bcs_content_type = mime.essence_str() == APPLICATION_BCS
+    || (mime.type_() == "application" && mime.suffix().is_some_and(|name| name == "bcs"));
 
-  is_binary_content_type
+  let is_binary_content_type = mime.essence_str() == mime::APPLICATION_OCTET_STREAM.essence_str()
+    || (mime.type_() == "application" && mime.suffix().is_some_and(|name| name == "octet-stream"));
+
+  match check {
+    ContentTypeCheck::Strict => is_bcs_content_type,
+    ContentTypeCheck::Lenient => is_bcs_content_type || is_binary_content_type,
+    ContentTypeCheck::Disabled => true,
+  }
 }
 
 impl<T> Deref for Bcs<T> {
@@ -110,7 +490,7 @@ where
       Ok(buf) => (
         [(
           header::CONTENT_TYPE,
-          HeaderValue::from_static(mime::APPLICATION_OCTET_STREAM.as_ref()),
+          HeaderValue::from_static(APPLICATION_BCS),
         )],
         Bytes::from(buf),
       ).into_response(),
@@ -0,0 +1,318 @@
+use std::convert::Infallible;
+
+use axum_core::{
+  extract::FromRequestParts,
+  response::{IntoResponse, Response},
+};
+use http::{HeaderMap, HeaderValue, StatusCode, header, request::Parts};
+use serde::Serialize;
+
+use crate::{
+  APPLICATION_BCS, BcsConfig, Encoding,
+  compression::{accept_encoding, compressed_response},
+};
+#[cfg(feature = "text")]
+use crate::TextEncoding;
+#[cfg(feature = "json")]
+use {
+  crate::{BcsRejection, bcs_body_type, bcs_config, decode, read_body},
+  axum_core::extract::{FromRequest, Request},
+  serde::de::DeserializeOwned,
+};
+
+#[cfg(feature = "text")]
+const APPLICATION_BCS_HEX: &str = "application/x-bcs+hex";
+#[cfg(feature = "text")]
+const APPLICATION_BCS_BASE64: &str = "application/x-bcs+base64";
+
+/// A body encoding the negotiating types can pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+  Bcs,
+  #[cfg(feature = "json")]
+  Json,
+  /// BCS sent as hex text, `application/x-bcs+hex`.
+  #[cfg(feature = "text")]
+  Hex,
+  /// BCS sent as standard base64 text, `application/x-bcs+base64`.
+  #[cfg(feature = "text")]
+  Base64,
+}
+
+impl Format {
+  /// Candidates in server preference order, used to break ties between equal qualities.
+  const ALL: &[Format] = &[
+    Format::Bcs,
+    #[cfg(feature = "json")]
+    Format::Json,
+    #[cfg(feature = "text")]
+    Format::Hex,
+    #[cfg(feature = "text")]
+    Format::Base64,
+  ];
+
+  fn media_types(self) -> &'static [(&'static str, &'static str)] {
+    match self {
+      Format::Bcs => &[("application", "x-bcs"), ("application", "octet-stream")],
+      #[cfg(feature = "json")]
+      Format::Json => &[("application", "json")],
+      #[cfg(feature = "text")]
+      Format::Hex => &[("application", "x-bcs+hex")],
+      #[cfg(feature = "text")]
+      Format::Base64 => &[("application", "x-bcs+base64")],
+    }
+  }
+}
+
+/// Extractor that picks a response [`Format`] from the request's `Accept` header.
+///
+/// The format with the highest quality wins; ties go to the more specific media range and
+/// then to BCS. Requests without an `Accept` header get BCS.
+///
+/// With a compression feature enabled, bodies of at least the route's
+/// `compression_threshold` bytes are also compressed with the best [`Encoding`] the
+/// `Accept-Encoding` header allows.
+#[derive(Debug, Clone, Copy)]
+pub struct Negotiate {
+  format: Option<Format>,
+  encoding: Option<Encoding>,
+  compression_threshold: usize,
+}
+
+impl Negotiate {
+  /// The negotiated format, or `None` if the client accepts none of them.
+  pub fn format(&self) -> Option<Format> {
+    self.format
+  }
+
+  /// The negotiated content encoding, or `None` if the response is sent uncompressed.
+  pub fn encoding(&self) -> Option<Encoding> {
+    self.encoding
+  }
+
+  pub fn respond<T>(&self, value: T) -> Negotiated<T> {
+    Negotiated {
+      negotiate: *self,
+      value,
+    }
+  }
+}
+
+impl<S> FromRequestParts<S> for Negotiate
+where
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, _s: &S) -> Result<Self, Self::Rejection> {
+    let compression_threshold = parts
+      .extensions
+      .get::<BcsConfig>()
+      .map_or(BcsConfig::default().compression_threshold, |config| config.compression_threshold);
+    Ok(Negotiate {
+      format: negotiate_format(&parts.headers),
+      encoding: accept_encoding(&parts.headers),
+      compression_threshold,
+    })
+  }
+}
+
+fn negotiate_format(headers: &HeaderMap) -> Option<Format> {
+  let ranges = accept_ranges(headers);
+  if ranges.is_empty() {
+    return Some(Format::Bcs);
+  }
+
+  let mut best: Option<(Format, u16, u8)> = None;
+  for &format in Format::ALL {
+    let Some((quality, specificity)) = format
+      .media_types()
+      .iter()
+      .filter_map(|&(type_, subtype)| match_quality(&ranges, type_, subtype))
+      .max_by_key(|&(quality, specificity)| (specificity, quality))
+    else {
+      continue;
+    };
+    if quality > 0 && best.is_none_or(|(_, q, s)| (quality, specificity) > (q, s)) {
+      best = Some((format, quality, specificity));
+    }
+  }
+  best.map(|(format, _, _)| format)
+}
+
+/// Returns the quality of the most specific range matching `type_/subtype`, along with how
+/// specific that range is.
+pub(crate) fn match_quality(ranges: &[(mime::Mime, u16)], type_: &str, subtype: &str) -> Option<(u16, u8)> {
+  ranges
+    .iter()
+    .filter_map(|(range, quality)| {
+      // Compare the subtype with its suffix, so `x-bcs+hex` does not match `x-bcs`.
+      let (_, full_subtype) = range.essence_str().split_once('/')?;
+      let specificity = match (range.type_().as_str(), full_subtype) {
+        ("*", "*") => 0,
+        (t, "*") if t == type_ => 1,
+        (t, s) if t == type_ && s == subtype => 2,
+        _ => return None,
+      };
+      Some((specificity, *quality))
+    })
+    .max_by_key(|&(specificity, _)| specificity)
+    .map(|(specificity, quality)| (quality, specificity))
+}
+
+pub(crate) fn accept_ranges(headers: &HeaderMap) -> Vec<(mime::Mime, u16)> {
+  headers
+    .get_all(header::ACCEPT)
+    .iter()
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .filter_map(|range| range.trim().parse::<mime::Mime>().ok())
+    .map(|range| {
+      let quality = range
+        .get_param("q")
+        .and_then(|q| q.as_str().parse::<f32>().ok())
+        .map_or(1000, |q| (q.clamp(0.0, 1.0) * 1000.0) as u16);
+      (range, quality)
+    })
+    .collect()
+}
+
+/// Response encoded in the format picked by [`Negotiate`].
+///
+/// Responds with `406 Not Acceptable` when no format was acceptable. Every response carries
+/// `Vary: Accept`, since its body depends on that header.
+pub struct Negotiated<T> {
+  negotiate: Negotiate,
+  value: T,
+}
+
+impl<T> IntoResponse for Negotiated<T>
+where
+  T: Serialize,
+{
+  fn into_response(self) -> Response {
+    let mut response = self.encode();
+    response
+      .headers_mut()
+      .append(header::VARY, HeaderValue::from_static("accept"));
+    response
+  }
+}
+
+impl<T> Negotiated<T>
+where
+  T: Serialize,
+{
+  fn encode(self) -> Response {
+    let (content_type, encoded) = match self.negotiate.format {
+      Some(Format::Bcs) => (
+        HeaderValue::from_static(APPLICATION_BCS),
+        bcs::to_bytes(&self.value).map_err(|err| err.to_string()),
+      ),
+      #[cfg(feature = "json")]
+      Some(Format::Json) => (
+        HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+        serde_json::to_vec(&self.value).map_err(|err| err.to_string()),
+      ),
+      #[cfg(feature = "text")]
+      Some(Format::Hex) => (
+        HeaderValue::from_static(APPLICATION_BCS_HEX),
+        encode_text(&self.value, TextEncoding::Hex),
+      ),
+      #[cfg(feature = "text")]
+      Some(Format::Base64) => (
+        HeaderValue::from_static(APPLICATION_BCS_BASE64),
+        encode_text(&self.value, TextEncoding::Base64),
+      ),
+      None => {
+        return (
+          StatusCode::NOT_ACCEPTABLE,
+          [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+          )],
+          "No acceptable response format",
+        ).into_response();
+      }
+    };
+
+    let buf = match encoded {
+      Ok(buf) => buf,
+      Err(err) => {
+        return (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+          )],
+          err,
+        ).into_response();
+      }
+    };
+
+    compressed_response(
+      content_type,
+      buf,
+      self.negotiate.encoding,
+      self.negotiate.compression_threshold,
+    )
+  }
+}
+
+#[cfg(feature = "text")]
+fn encode_text<T>(value: &T, encoding: TextEncoding) -> Result<Vec<u8>, String>
+where
+  T: Serialize,
+{
+  bcs::to_bytes(value)
+    .map(|bytes| encoding.encode(bytes).into_bytes())
+    .map_err(|err| err.to_string())
+}
+
+/// Extractor that decodes JSON or BCS depending on the request `Content-Type`.
+///
+/// JSON bodies need `application/json` or a `+json` suffix; everything else goes through
+/// the same checks and limits as [`Bcs`](crate::Bcs).
+#[cfg(feature = "json")]
+pub struct BcsOrJson<T>(pub T);
+
+#[cfg(feature = "json")]
+impl<S, T> FromRequest<S> for BcsOrJson<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if json_content_type(req.headers()) {
+      let bytes = read_body(req, _s, &config).await?;
+      Ok(BcsOrJson(serde_json::from_slice(&bytes)?))
+    } else if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      decode(&bytes, &config).map(BcsOrJson)
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
+
+#[cfg(feature = "json")]
+fn json_content_type(headers: &HeaderMap) -> bool {
+  let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
+    return false;
+  };
+
+  let Ok(content_type) = content_type.to_str() else {
+    return false;
+  };
+
+  let Ok(mime) = content_type.parse::<mime::Mime>() else {
+    return false;
+  };
+
+  mime.type_() == "application"
+    && (mime.subtype() == "json" || mime.suffix().is_some_and(|name| name == "json"))
+}
@@ -0,0 +1,165 @@
+use std::any::type_name;
+
+use crate::{APPLICATION_BCS, Bcs, BcsRejection};
+
+/// The last path segment of `T`'s name, without generics.
+fn short_type_name<T>() -> &'static str {
+  let name = type_name::<T>();
+  let name = name.split_once('<').map_or(name, |(name, _)| name);
+  name.rsplit_once("::").map_or(name, |(_, name)| name)
+}
+
+fn bcs_description<T>() -> String {
+  format!("BCS-encoded `{}`", type_name::<T>())
+}
+
+#[cfg(feature = "aide")]
+mod aide_impls {
+  use aide::{
+    OperationInput, OperationOutput,
+    generate::GenContext,
+    openapi::{MediaType, Operation, RequestBody, Response, SchemaObject},
+    operation::set_body,
+  };
+
+  use super::*;
+
+  fn bcs_media_type<T>() -> MediaType {
+    MediaType {
+      schema: Some(SchemaObject {
+        json_schema: schemars::json_schema!({
+          "type": "string",
+          "format": "binary",
+          "title": short_type_name::<T>(),
+          "description": bcs_description::<T>(),
+        }),
+        example: None,
+        external_docs: None,
+      }),
+      ..Default::default()
+    }
+  }
+
+  fn rejection_responses(ctx: &mut GenContext, operation: &mut Operation) -> Vec<(Option<u16>, Response)> {
+    let Some(response) = String::operation_response(ctx, operation) else {
+      return Vec::new();
+    };
+    BcsRejection::STATUS_CODES
+      .iter()
+      .map(|status| {
+        let description = status.canonical_reason().unwrap_or_default().to_owned();
+        (Some(status.as_u16()), Response {
+          description,
+          ..response.clone()
+        })
+      })
+      .collect()
+  }
+
+  impl<T> OperationInput for Bcs<T> {
+    fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+      set_body(ctx, operation, RequestBody {
+        description: Some(bcs_description::<T>()),
+        content: [(APPLICATION_BCS.to_owned(), bcs_media_type::<T>())].into_iter().collect(),
+        required: true,
+        extensions: Default::default(),
+      });
+    }
+
+    fn inferred_early_responses(ctx: &mut GenContext, operation: &mut Operation) -> Vec<(Option<u16>, Response)> {
+      rejection_responses(ctx, operation)
+    }
+  }
+
+  impl<T> OperationOutput for Bcs<T> {
+    type Inner = T;
+
+    fn operation_response(_ctx: &mut GenContext, _operation: &mut Operation) -> Option<Response> {
+      Some(Response {
+        description: bcs_description::<T>(),
+        content: [(APPLICATION_BCS.to_owned(), bcs_media_type::<T>())].into_iter().collect(),
+        ..Default::default()
+      })
+    }
+
+    fn inferred_responses(ctx: &mut GenContext, operation: &mut Operation) -> Vec<(Option<u16>, Response)> {
+      Self::operation_response(ctx, operation)
+        .map(|response| vec![(Some(200), response)])
+        .unwrap_or_default()
+    }
+  }
+
+  impl OperationOutput for BcsRejection {
+    type Inner = Self;
+
+    fn operation_response(ctx: &mut GenContext, operation: &mut Operation) -> Option<Response> {
+      String::operation_response(ctx, operation)
+    }
+
+    fn inferred_responses(ctx: &mut GenContext, operation: &mut Operation) -> Vec<(Option<u16>, Response)> {
+      rejection_responses(ctx, operation)
+    }
+  }
+}
+
+#[cfg(feature = "utoipa")]
+mod utoipa_impls {
+  use std::{borrow::Cow, collections::BTreeMap};
+
+  use utoipa::{
+    IntoResponses, PartialSchema, ToSchema,
+    openapi::{
+      ContentBuilder, ObjectBuilder, RefOr, ResponseBuilder, Type,
+      response::Response,
+      schema::{KnownFormat, Schema, SchemaFormat},
+    },
+  };
+
+  use super::*;
+
+  impl<T> PartialSchema for Bcs<T> {
+    fn schema() -> RefOr<Schema> {
+      ObjectBuilder::new()
+        .schema_type(Type::String)
+        .format(Some(SchemaFormat::KnownFormat(KnownFormat::Binary)))
+        .title(Some(short_type_name::<T>()))
+        .description(Some(bcs_description::<T>()))
+        .content_media_type(APPLICATION_BCS)
+        .into()
+    }
+  }
+
+  impl<T> ToSchema for Bcs<T> {
+    fn name() -> Cow<'static, str> {
+      Cow::Owned(format!("Bcs{}", short_type_name::<T>()))
+    }
+  }
+
+  impl<T> IntoResponses for Bcs<T> {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+      let response = ResponseBuilder::new()
+        .description(bcs_description::<T>())
+        .content(APPLICATION_BCS, ContentBuilder::new().schema(Some(Self::schema())).build())
+        .build();
+      BTreeMap::from([("200".to_owned(), response.into())])
+    }
+  }
+
+  impl IntoResponses for BcsRejection {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+      BcsRejection::STATUS_CODES
+        .iter()
+        .map(|status| {
+          let response = ResponseBuilder::new()
+            .description(status.canonical_reason().unwrap_or_default())
+            .content(
+              mime::TEXT_PLAIN_UTF_8.as_ref(),
+              ContentBuilder::new().schema(Some(String::schema())).build(),
+            )
+            .build();
+          (status.as_str().to_owned(), response.into())
+        })
+        .collect()
+    }
+  }
+}
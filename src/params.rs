@@ -0,0 +1,89 @@
+use std::marker::PhantomData;
+
+use axum_core::extract::FromRequestParts;
+use http::request::Parts;
+use serde::de::DeserializeOwned;
+
+use crate::{BcsConfig, BcsRejection, TextEncoding, decode};
+
+/// Marker type naming the parameter a [`BcsQuery`] or [`BcsPath`] is read from, and how it
+/// is encoded.
+pub trait BcsParamName {
+  const NAME: &'static str;
+  const ENCODING: TextEncoding = TextEncoding::Base64Url;
+}
+
+/// The parameter named `bcs`, encoded as base64url.
+pub struct DefaultBcsParam;
+
+impl BcsParamName for DefaultBcsParam {
+  const NAME: &'static str = "bcs";
+}
+
+/// Extractor that decodes a text-encoded BCS value from the query parameter named by `N`,
+/// e.g. `?bcs=AQID` by default.
+pub struct BcsQuery<T, N = DefaultBcsParam>(pub T, pub PhantomData<N>);
+
+impl<S, T, N> FromRequestParts<S> for BcsQuery<T, N>
+where
+  T: DeserializeOwned,
+  N: BcsParamName,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request_parts(parts: &mut Parts, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = parts_config(parts);
+    let value = form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+      .find(|(name, _)| *name == N::NAME)
+      .map(|(_, value)| value)
+      .ok_or_else(|| BcsRejection::MissingParam {
+        name: N::NAME.to_owned(),
+      })?;
+    decode_param::<T, N>(value.as_bytes(), &config).map(|value| BcsQuery(value, PhantomData))
+  }
+}
+
+/// Extractor that decodes a text-encoded BCS value from the path parameter named by `N`,
+/// e.g. `/items/{bcs}` by default.
+#[cfg(feature = "path")]
+pub struct BcsPath<T, N = DefaultBcsParam>(pub T, pub PhantomData<N>);
+
+#[cfg(feature = "path")]
+impl<S, T, N> FromRequestParts<S> for BcsPath<T, N>
+where
+  T: DeserializeOwned,
+  N: BcsParamName,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request_parts(parts: &mut Parts, s: &S) -> Result<Self, Self::Rejection> {
+    let config = parts_config(parts);
+    let missing = || BcsRejection::MissingParam {
+      name: N::NAME.to_owned(),
+    };
+    let params = axum::extract::RawPathParams::from_request_parts(parts, s)
+      .await
+      .map_err(|_| missing())?;
+    let value = params
+      .iter()
+      .find(|(name, _)| *name == N::NAME)
+      .map(|(_, value)| value)
+      .ok_or_else(missing)?;
+    decode_param::<T, N>(value.as_bytes(), &config).map(|value| BcsPath(value, PhantomData))
+  }
+}
+
+pub(crate) fn parts_config(parts: &Parts) -> BcsConfig {
+  parts.extensions.get::<BcsConfig>().cloned().unwrap_or_default()
+}
+
+fn decode_param<T, N>(text: &[u8], config: &BcsConfig) -> Result<T, BcsRejection>
+where
+  T: DeserializeOwned,
+  N: BcsParamName,
+{
+  let bytes = N::ENCODING.decode(text)?;
+  decode(&bytes, config)
+}
@@ -0,0 +1,30 @@
+use axum_core::extract::{FromRequest, Request};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+
+use crate::{BcsRejection, bcs_body_type, bcs_config, decode_prefix, read_body};
+
+/// Extractor that decodes a leading BCS value and keeps the bytes after it.
+///
+/// Unlike [`Bcs`](crate::Bcs), trailing bytes are not an error; they are handed to the
+/// handler as the second field, e.g. a signature appended to the payload.
+pub struct BcsPrefix<T>(pub T, pub Bytes);
+
+impl<S, T> FromRequest<S> for BcsPrefix<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      let (value, consumed) = decode_prefix(&bytes, &config)?;
+      Ok(BcsPrefix(value, bytes.slice(consumed..)))
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
@@ -0,0 +1,270 @@
+use axum_core::response::{IntoResponse, Response};
+use bytes::Bytes;
+use http::{HeaderValue, header};
+use serde::{Deserialize, Serialize};
+
+use crate::{APPLICATION_BCS, BcsFieldError, BcsRejection};
+
+/// Machine-readable description of a [`BcsRejection`].
+///
+/// New kinds are only ever appended, so the BCS encoding stays stable for clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BcsErrorBody {
+  pub code: String,
+  pub status: u16,
+  pub kind: BcsErrorKind,
+  pub detail: String,
+  pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum BcsErrorKind {
+  BytesRead,
+  MissingContentType,
+  BcsError(BcsDecodeErrorKind),
+  ContainerDepthExceeded { limit: u64 },
+  PayloadTooLarge { limit: u64 },
+  TrailingBytes { consumed: u64, remaining: u64 },
+  FrameTooLarge { limit: u64 },
+  StreamRead,
+  JsonError,
+  MissingDigest,
+  DigestMismatch,
+  InvalidSignature,
+  UnsupportedEncoding,
+  Decompression,
+  DecompressedTooLarge { limit: u64 },
+  InvalidSchemaVersion,
+  UnsupportedSchemaVersion { version: u32 },
+  SchemaMismatch { expected: String },
+  SchemaTrace,
+  Validation { errors: Vec<BcsFieldError> },
+  NonCanonical,
+  MissingParam { name: String },
+  InvalidTextEncoding,
+  MissingHeader { name: String },
+}
+
+/// Mirrors the variants of [`bcs::Error`] without their payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BcsDecodeErrorKind {
+  Eof,
+  Io,
+  ExceededMaxLen,
+  ExceededContainerDepthLimit,
+  ExpectedBoolean,
+  ExpectedMapKey,
+  ExpectedMapValue,
+  NonCanonicalMap,
+  ExpectedOption,
+  Custom,
+  MissingLen,
+  NotSupported,
+  RemainingInput,
+  Utf8,
+  NonCanonicalUleb128Encoding,
+  IntegerOverflowDuringUleb128Decoding,
+}
+
+impl From<&bcs::Error> for BcsDecodeErrorKind {
+  fn from(err: &bcs::Error) -> Self {
+    match err {
+      bcs::Error::Eof => Self::Eof,
+      bcs::Error::Io(_) => Self::Io,
+      bcs::Error::ExceededMaxLen(_) => Self::ExceededMaxLen,
+      bcs::Error::ExceededContainerDepthLimit(_) => Self::ExceededContainerDepthLimit,
+      bcs::Error::ExpectedBoolean => Self::ExpectedBoolean,
+      bcs::Error::ExpectedMapKey => Self::ExpectedMapKey,
+      bcs::Error::ExpectedMapValue => Self::ExpectedMapValue,
+      bcs::Error::NonCanonicalMap => Self::NonCanonicalMap,
+      bcs::Error::ExpectedOption => Self::ExpectedOption,
+      bcs::Error::Custom(_) => Self::Custom,
+      bcs::Error::MissingLen => Self::MissingLen,
+      bcs::Error::NotSupported(_) => Self::NotSupported,
+      bcs::Error::RemainingInput => Self::RemainingInput,
+      bcs::Error::Utf8 => Self::Utf8,
+      bcs::Error::NonCanonicalUleb128Encoding => Self::NonCanonicalUleb128Encoding,
+      bcs::Error::IntegerOverflowDuringUleb128Decoding => Self::IntegerOverflowDuringUleb128Decoding,
+    }
+  }
+}
+
+impl BcsRejection {
+  /// A stable identifier for the rejection kind.
+  pub fn code(&self) -> &'static str {
+    match self {
+      Self::BytesRead(_) => "bytes_read",
+      Self::MissingContentType => "missing_content_type",
+      Self::BcsError { .. } => "bcs_error",
+      Self::ContainerDepthExceeded { .. } => "container_depth_exceeded",
+      Self::PayloadTooLarge { .. } => "payload_too_large",
+      Self::TrailingBytes { .. } => "trailing_bytes",
+      Self::FrameTooLarge { .. } => "frame_too_large",
+      Self::StreamRead(_) => "stream_read",
+      #[cfg(feature = "json")]
+      Self::JsonError(_) => "json_error",
+      Self::MissingDigest => "missing_digest",
+      Self::DigestMismatch => "digest_mismatch",
+      Self::InvalidSignature => "invalid_signature",
+      Self::UnsupportedEncoding => "unsupported_encoding",
+      Self::Decompression(_) => "decompression",
+      Self::DecompressedTooLarge { .. } => "decompressed_too_large",
+      Self::InvalidSchemaVersion => "invalid_schema_version",
+      Self::UnsupportedSchemaVersion { .. } => "unsupported_schema_version",
+      #[cfg(feature = "reflection")]
+      Self::SchemaMismatch { .. } => "schema_mismatch",
+      #[cfg(feature = "reflection")]
+      Self::SchemaTrace(_) => "schema_trace",
+      Self::Validation(_) => "validation",
+      Self::NonCanonical { .. } => "non_canonical",
+      Self::MissingParam { .. } => "missing_param",
+      Self::InvalidTextEncoding => "invalid_text_encoding",
+      Self::MissingHeader { .. } => "missing_header",
+    }
+  }
+
+  pub fn kind(&self) -> BcsErrorKind {
+    match self {
+      Self::BytesRead(_) => BcsErrorKind::BytesRead,
+      Self::MissingContentType => BcsErrorKind::MissingContentType,
+      Self::BcsError { source, .. } => BcsErrorKind::BcsError(source.into()),
+      Self::ContainerDepthExceeded { limit } => BcsErrorKind::ContainerDepthExceeded { limit: *limit as u64 },
+      Self::PayloadTooLarge { limit } => BcsErrorKind::PayloadTooLarge { limit: *limit as u64 },
+      Self::TrailingBytes { consumed, remaining } => BcsErrorKind::TrailingBytes {
+        consumed: *consumed as u64,
+        remaining: *remaining as u64,
+      },
+      Self::FrameTooLarge { limit } => BcsErrorKind::FrameTooLarge { limit: *limit as u64 },
+      Self::StreamRead(_) => BcsErrorKind::StreamRead,
+      #[cfg(feature = "json")]
+      Self::JsonError(_) => BcsErrorKind::JsonError,
+      Self::MissingDigest => BcsErrorKind::MissingDigest,
+      Self::DigestMismatch => BcsErrorKind::DigestMismatch,
+      Self::InvalidSignature => BcsErrorKind::InvalidSignature,
+      Self::UnsupportedEncoding => BcsErrorKind::UnsupportedEncoding,
+      Self::Decompression(_) => BcsErrorKind::Decompression,
+      Self::DecompressedTooLarge { limit } => BcsErrorKind::DecompressedTooLarge { limit: *limit as u64 },
+      Self::InvalidSchemaVersion => BcsErrorKind::InvalidSchemaVersion,
+      Self::UnsupportedSchemaVersion { version } => BcsErrorKind::UnsupportedSchemaVersion { version: *version },
+      #[cfg(feature = "reflection")]
+      Self::SchemaMismatch { expected } => BcsErrorKind::SchemaMismatch {
+        expected: expected.clone(),
+      },
+      #[cfg(feature = "reflection")]
+      Self::SchemaTrace(_) => BcsErrorKind::SchemaTrace,
+      Self::Validation(errors) => BcsErrorKind::Validation {
+        errors: errors.clone(),
+      },
+      Self::NonCanonical { .. } => BcsErrorKind::NonCanonical,
+      Self::MissingParam { name } => BcsErrorKind::MissingParam { name: name.clone() },
+      Self::InvalidTextEncoding => BcsErrorKind::InvalidTextEncoding,
+      Self::MissingHeader { name } => BcsErrorKind::MissingHeader { name: name.clone() },
+    }
+  }
+
+  /// Byte offset into the body where decoding failed, when known.
+  pub fn offset(&self) -> Option<usize> {
+    match self {
+      Self::BcsError { offset, .. } => *offset,
+      Self::TrailingBytes { consumed, .. } => Some(*consumed),
+      Self::NonCanonical { offset } => Some(*offset),
+      _ => None,
+    }
+  }
+
+  pub fn error_body(&self) -> BcsErrorBody {
+    BcsErrorBody {
+      code: self.code().to_owned(),
+      status: self.status().as_u16(),
+      kind: self.kind(),
+      detail: self.body_text(),
+      offset: self.offset().map(|offset| offset as u64),
+    }
+  }
+}
+
+/// Responds to a [`BcsRejection`] with a BCS-encoded [`BcsErrorBody`].
+///
+/// Use it in place of the plain-text rejection, e.g. with `axum_extra::extract::WithRejection`.
+#[derive(Debug)]
+pub struct BcsErrorResponse(pub BcsRejection);
+
+impl From<BcsRejection> for BcsErrorResponse {
+  fn from(rejection: BcsRejection) -> Self {
+    Self(rejection)
+  }
+}
+
+impl IntoResponse for BcsErrorResponse {
+  fn into_response(self) -> Response {
+    match bcs::to_bytes(&self.0.error_body()) {
+      Ok(buf) => (
+        self.0.status(),
+        [(
+          header::CONTENT_TYPE,
+          HeaderValue::from_static(APPLICATION_BCS),
+        )],
+        Bytes::from(buf),
+      ).into_response(),
+      Err(_) => self.0.into_response(),
+    }
+  }
+}
+
+/// Responds to a [`BcsRejection`] with an RFC 9457 `application/problem+json` document.
+///
+/// The [`BcsErrorBody`] fields other than `status` and `detail` are added as extension members.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct ProblemJson(pub BcsRejection);
+
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct ProblemDetails<'a> {
+  #[serde(rename = "type")]
+  type_: &'a str,
+  title: &'a str,
+  status: u16,
+  detail: String,
+  code: String,
+  kind: BcsErrorKind,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  offset: Option<u64>,
+}
+
+#[cfg(feature = "json")]
+impl From<BcsRejection> for ProblemJson {
+  fn from(rejection: BcsRejection) -> Self {
+    Self(rejection)
+  }
+}
+
+#[cfg(feature = "json")]
+impl IntoResponse for ProblemJson {
+  fn into_response(self) -> Response {
+    let status = self.0.status();
+    let body = self.0.error_body();
+    let problem = ProblemDetails {
+      type_: "about:blank",
+      title: status.canonical_reason().unwrap_or_default(),
+      status: body.status,
+      detail: body.detail,
+      code: body.code,
+      kind: body.kind,
+      offset: body.offset,
+    };
+
+    match serde_json::to_vec(&problem) {
+      Ok(buf) => (
+        status,
+        [(
+          header::CONTENT_TYPE,
+          HeaderValue::from_static("application/problem+json"),
+        )],
+        Bytes::from(buf),
+      ).into_response(),
+      Err(_) => self.0.into_response(),
+    }
+  }
+}
@@ -0,0 +1,150 @@
+use std::{
+  any::TypeId,
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+};
+
+use axum_core::{
+  extract::{FromRequest, Request},
+  response::{IntoResponse, Response},
+};
+use http::{HeaderName, HeaderValue};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_reflection::{Format, FormatHolder, Registry, Tracer, TracerConfig};
+use sha2::{Digest, Sha256};
+
+use crate::{Bcs, BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// Header carrying the hex-encoded schema fingerprint of a BCS body.
+pub const X_BCS_SCHEMA_FINGERPRINT: HeaderName = HeaderName::from_static("x-bcs-schema-fingerprint");
+
+/// A type whose schema can be traced with `serde-reflection`.
+///
+/// Tracing visits one variant of an enum per pass, so enums nested inside `Self` have to be
+/// traced on their own first; list them in [`trace_nested`](BcsReflect::trace_nested).
+/// Types without nested enums only need an empty impl.
+pub trait BcsReflect: DeserializeOwned + 'static {
+  /// Traces the enums contained in `Self`, e.g. with `tracer.trace_simple_type::<MyEnum>()`.
+  fn trace_nested(tracer: &mut Tracer) -> serde_reflection::Result<()> {
+    let _ = tracer;
+    Ok(())
+  }
+}
+
+/// The serde format of a type, as traced by `serde-reflection`.
+#[derive(Debug, Serialize)]
+pub struct BcsSchema {
+  /// Format of the type itself, usually a name looked up in `registry`.
+  pub root: Format,
+  /// Formats of every named container the type refers to.
+  pub registry: Registry,
+  #[serde(skip)]
+  fingerprint: String,
+}
+
+impl BcsSchema {
+  /// Traces the schema of `T`.
+  pub fn trace<T>() -> Result<BcsSchema, serde_reflection::Error>
+  where
+    T: BcsReflect,
+  {
+    let mut tracer = Tracer::new(TracerConfig::default());
+    T::trace_nested(&mut tracer)?;
+    let (mut root, _) = tracer.trace_simple_type::<T>()?;
+    let registry = tracer.registry()?;
+    root.normalize()?;
+
+    let encoded = bcs::to_bytes(&(&root, &registry))
+      .map_err(|err| serde_reflection::Error::Custom(err.to_string()))?;
+    let fingerprint = hex::encode(Sha256::digest(encoded));
+    Ok(BcsSchema {
+      root,
+      registry,
+      fingerprint,
+    })
+  }
+
+  /// Hex-encoded SHA-256 of the BCS-encoded root format and registry.
+  ///
+  /// Any change to the layout of the type or of a type it contains changes the fingerprint.
+  pub fn fingerprint(&self) -> &str {
+    &self.fingerprint
+  }
+
+  /// The [`fingerprint`](BcsSchema::fingerprint) of `T`, traced once per type and cached.
+  pub fn fingerprint_of<T>() -> Result<&'static str, BcsRejection>
+  where
+    T: BcsReflect,
+  {
+    type Cache = HashMap<TypeId, Result<&'static str, String>>;
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap_or_else(|err| err.into_inner());
+    cache
+      .entry(TypeId::of::<T>())
+      .or_insert_with(|| {
+        BcsSchema::trace::<T>()
+          .map(|schema| &*schema.fingerprint.leak())
+          .map_err(|err| err.to_string())
+      })
+      .clone()
+      .map_err(BcsRejection::SchemaTrace)
+  }
+}
+
+/// Extractor and response for BCS bodies checked against a schema fingerprint.
+///
+/// When the request has an [`X_BCS_SCHEMA_FINGERPRINT`] header, it must match the
+/// [`BcsSchema::fingerprint`] of `T`, or the request is rejected with `409 Conflict`
+/// before the body is decoded. Responses carry the fingerprint in the same header.
+pub struct FingerprintedBcs<T>(pub T);
+
+impl<S, T> FromRequest<S> for FingerprintedBcs<T>
+where
+  T: BcsReflect,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if !bcs_body_type(req.headers(), &config) {
+      return Err(BcsRejection::MissingContentType);
+    }
+
+    if let Some(received) = req.headers().get(X_BCS_SCHEMA_FINGERPRINT) {
+      let expected = BcsSchema::fingerprint_of::<T>()?;
+      let matches = received
+        .to_str()
+        .is_ok_and(|received| received.eq_ignore_ascii_case(expected));
+      if !matches {
+        return Err(BcsRejection::SchemaMismatch {
+          expected: expected.to_owned(),
+        });
+      }
+    }
+
+    let bytes = read_body(req, _s, &config).await?;
+    decode(&bytes, &config).map(FingerprintedBcs)
+  }
+}
+
+impl<T> IntoResponse for FingerprintedBcs<T>
+where
+  T: BcsReflect + Serialize,
+{
+  fn into_response(self) -> Response {
+    let fingerprint = match BcsSchema::fingerprint_of::<T>() {
+      Ok(fingerprint) => fingerprint,
+      Err(rejection) => return rejection.into_response(),
+    };
+
+    let mut response = Bcs(self.0).into_response();
+    if response.status().is_success()
+      && let Ok(value) = HeaderValue::from_str(fingerprint)
+    {
+      response.headers_mut().insert(X_BCS_SCHEMA_FINGERPRINT, value);
+    }
+    response
+  }
+}
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+
+use axum_core::{
+  extract::{FromRef, FromRequest, FromRequestParts, Request},
+  response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header, request::Parts};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{APPLICATION_BCS, BcsConfig, BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// Header carrying the hex-encoded Ed25519 signature of a BCS body.
+pub const X_BCS_SIGNATURE: HeaderName = HeaderName::from_static("x-bcs-signature");
+/// Header carrying the hex-encoded Ed25519 public key that signed a BCS body.
+pub const X_BCS_PUBLIC_KEY: HeaderName = HeaderName::from_static("x-bcs-public-key");
+
+/// BCS envelope carrying a payload together with its signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedEnvelope {
+  pub payload: Vec<u8>,
+  pub public_key: [u8; 32],
+  pub signature: Vec<u8>,
+}
+
+/// Extractor that verifies an Ed25519 signature before decoding the BCS payload.
+///
+/// If the request has [`X_BCS_SIGNATURE`] and [`X_BCS_PUBLIC_KEY`] headers, the body is the
+/// payload; otherwise the body is a [`SignedEnvelope`]. The signature covers the route's
+/// `signature_domain` followed by the payload bytes. The handler receives the verified key
+/// and decides whether to trust it.
+pub struct SignedBcs<T>(pub T, pub VerifyingKey);
+
+impl<S, T> FromRequest<S> for SignedBcs<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if !bcs_body_type(req.headers(), &config) {
+      return Err(BcsRejection::MissingContentType);
+    }
+
+    let headers = signature_headers(req.headers())?;
+    let bytes = read_body(req, _s, &config).await?;
+    let (payload, (public_key, signature)) = match headers {
+      Some(headers) => (bytes, headers),
+      None => {
+        let envelope: SignedEnvelope = decode(&bytes, &config)?;
+        let parts = signature_parts(&envelope.public_key, &envelope.signature)?;
+        (Bytes::from(envelope.payload), parts)
+      }
+    };
+
+    let mut message = Vec::with_capacity(config.signature_domain.len() + payload.len());
+    message.extend_from_slice(&config.signature_domain);
+    message.extend_from_slice(&payload);
+    public_key
+      .verify_strict(&message, &signature)
+      .map_err(|_| BcsRejection::InvalidSignature)?;
+
+    let value = decode(&payload, &config)?;
+    Ok(SignedBcs(value, public_key))
+  }
+}
+
+fn signature_parts(public_key: &[u8], signature: &[u8]) -> Result<(VerifyingKey, Signature), BcsRejection> {
+  let public_key = <[u8; 32]>::try_from(public_key)
+    .ok()
+    .and_then(|key| VerifyingKey::from_bytes(&key).ok())
+    .ok_or(BcsRejection::InvalidSignature)?;
+  let signature = Signature::from_slice(signature).map_err(|_| BcsRejection::InvalidSignature)?;
+  Ok((public_key, signature))
+}
+
+/// Reads the signature headers, returning `None` when neither is present.
+fn signature_headers(headers: &HeaderMap) -> Result<Option<(VerifyingKey, Signature)>, BcsRejection> {
+  let hex_header = |name: &HeaderName| {
+    headers.get(name).map(|value| {
+      value
+        .to_str()
+        .ok()
+        .and_then(|value| hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok())
+        .ok_or(BcsRejection::InvalidSignature)
+    })
+  };
+
+  match (hex_header(&X_BCS_PUBLIC_KEY), hex_header(&X_BCS_SIGNATURE)) {
+    (Some(public_key), Some(signature)) => signature_parts(&public_key?, &signature?).map(Some),
+    (None, None) => Ok(None),
+    _ => Err(BcsRejection::InvalidSignature),
+  }
+}
+
+/// The server's Ed25519 key, held in router state.
+///
+/// Implement `FromRef<YourState>` for it to use [`BcsSigner`] in handlers.
+#[derive(Clone)]
+pub struct BcsSigningKey(pub Arc<SigningKey>);
+
+impl BcsSigningKey {
+  pub fn new(key: SigningKey) -> Self {
+    Self(Arc::new(key))
+  }
+}
+
+/// Extractor that signs BCS responses with the [`BcsSigningKey`] from router state.
+pub struct BcsSigner {
+  key: BcsSigningKey,
+  domain: Bytes,
+}
+
+impl BcsSigner {
+  pub fn sign<T>(&self, value: T) -> SignedBcsResponse<T> {
+    SignedBcsResponse {
+      key: self.key.clone(),
+      domain: self.domain.clone(),
+      value,
+    }
+  }
+}
+
+impl<S> FromRequestParts<S> for BcsSigner
+where
+  BcsSigningKey: FromRef<S>,
+  S: Send + Sync,
+{
+  type Rejection = std::convert::Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let domain = parts
+      .extensions
+      .get::<BcsConfig>()
+      .map(|config| config.signature_domain.clone())
+      .unwrap_or_default();
+    Ok(BcsSigner {
+      key: BcsSigningKey::from_ref(state),
+      domain,
+    })
+  }
+}
+
+/// BCS response signed by a [`BcsSigner`].
+///
+/// The body is the plain BCS encoding of the value, with the signature and public key in
+/// the [`X_BCS_SIGNATURE`] and [`X_BCS_PUBLIC_KEY`] headers.
+pub struct SignedBcsResponse<T> {
+  key: BcsSigningKey,
+  domain: Bytes,
+  value: T,
+}
+
+impl<T> IntoResponse for SignedBcsResponse<T>
+where
+  T: Serialize,
+{
+  fn into_response(self) -> Response {
+    match bcs::to_bytes(&self.value) {
+      Ok(buf) => {
+        let mut message = Vec::with_capacity(self.domain.len() + buf.len());
+        message.extend_from_slice(&self.domain);
+        message.extend_from_slice(&buf);
+        let signature = self.key.0.sign(&message);
+        let public_key = self.key.0.verifying_key();
+
+        (
+          [
+            (
+              header::CONTENT_TYPE,
+              HeaderValue::from_static(APPLICATION_BCS),
+            ),
+            (
+              X_BCS_SIGNATURE,
+              HeaderValue::try_from(hex::encode(signature.to_bytes())).expect("hex is a valid header value"),
+            ),
+            (
+              X_BCS_PUBLIC_KEY,
+              HeaderValue::try_from(hex::encode(public_key.to_bytes())).expect("hex is a valid header value"),
+            ),
+          ],
+          Bytes::from(buf),
+        ).into_response()
+      }
+      Err(err) => (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        [(
+          header::CONTENT_TYPE,
+          HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+        )],
+        err.to_string(),
+      ).into_response(),
+    }
+  }
+}
@@ -0,0 +1,273 @@
+use std::{
+  marker::PhantomData,
+  pin::Pin,
+  task::{Context, Poll, ready},
+};
+
+use axum_core::{
+  BoxError, RequestExt,
+  body::{Body, BodyDataStream},
+  extract::{FromRequest, Request},
+  response::{IntoResponse, Response},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use futures_core::Stream;
+use futures_util::StreamExt;
+use http::{HeaderValue, header};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+  APPLICATION_BCS, BcsConfig, BcsRejection, bcs_config, bcs_content_type, compression::is_identity, decode,
+  exceeded_body_limit, limit_body, text::text_transport,
+};
+
+/// Response that encodes the items of a stream one chunk at a time.
+///
+/// With a known item count ([`BcsStream::with_len`]) the body is a regular BCS sequence,
+/// i.e. the same bytes `Bcs<Vec<T>>` would produce. Otherwise every item is sent as a
+/// frame prefixed with its ULEB128-encoded byte length.
+pub struct BcsStream<S> {
+  stream: S,
+  len: Option<usize>,
+}
+
+impl<S> BcsStream<S> {
+  /// Sends each item as a length-delimited frame.
+  pub fn new(stream: S) -> Self {
+    Self { stream, len: None }
+  }
+
+  /// Sends a BCS sequence of exactly `len` items.
+  ///
+  /// The body ends with an error if the stream yields a different number of items.
+  pub fn with_len(stream: S, len: usize) -> Self {
+    Self {
+      stream,
+      len: Some(len),
+    }
+  }
+}
+
+struct EncodeState<S> {
+  stream: Pin<Box<S>>,
+  len: Option<usize>,
+  sent: usize,
+  started: bool,
+  done: bool,
+}
+
+impl<S, T> IntoResponse for BcsStream<S>
+where
+  S: Stream<Item = T> + Send + 'static,
+  T: Serialize,
+{
+  fn into_response(self) -> Response {
+    let state = EncodeState {
+      stream: Box::pin(self.stream),
+      len: self.len,
+      sent: 0,
+      started: false,
+      done: false,
+    };
+
+    let body = futures_util::stream::unfold(state, |mut state| async move {
+      if state.done {
+        return None;
+      }
+
+      let mut buf = Vec::new();
+      if !state.started {
+        state.started = true;
+        if let Some(len) = state.len {
+          write_uleb128(&mut buf, len);
+        }
+      }
+
+      match state.stream.next().await {
+        Some(_) if state.len.is_some_and(|len| state.sent == len) => {
+          state.done = true;
+          Some((Err(BoxError::from("stream yielded more items than announced")), state))
+        }
+        Some(item) => {
+          state.sent += 1;
+          match encode_item(&mut buf, &item, state.len.is_none()) {
+            Ok(()) => Some((Ok(Bytes::from(buf)), state)),
+            Err(err) => {
+              state.done = true;
+              Some((Err(BoxError::from(err)), state))
+            }
+          }
+        }
+        None if state.len.is_some_and(|len| state.sent != len) => {
+          state.done = true;
+          Some((Err(BoxError::from("stream yielded fewer items than announced")), state))
+        }
+        None => {
+          state.done = true;
+          (!buf.is_empty()).then(|| (Ok(Bytes::from(buf)), state))
+        }
+      }
+    });
+
+    (
+      [(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(APPLICATION_BCS),
+      )],
+      Body::from_stream(body),
+    ).into_response()
+  }
+}
+
+fn encode_item<T>(buf: &mut Vec<u8>, item: &T, framed: bool) -> Result<(), bcs::Error>
+where
+  T: Serialize,
+{
+  if framed {
+    write_uleb128(buf, bcs::serialized_size(item)?);
+  }
+  bcs::serialize_into(buf, item)
+}
+
+pub(crate) fn write_uleb128(buf: &mut Vec<u8>, mut value: usize) {
+  while value >= 0x80 {
+    buf.push((value & 0x7f) as u8 | 0x80);
+    value >>= 7;
+  }
+  buf.push(value as u8);
+}
+
+/// Extractor that decodes a request body of length-delimited frames as they arrive.
+///
+/// Every frame is a ULEB128-encoded byte length followed by that many bytes of BCS, the
+/// format [`BcsStream::new`] produces. The extractor is a [`Stream`] of decoded values;
+/// the route's [`BcsConfig`] limits apply to each frame, and both its body limit and axum's
+/// `DefaultBodyLimit` to the whole body. The stream ends after the first error. Compressed bodies are not supported.
+pub struct BcsFrames<T> {
+  stream: BodyDataStream,
+  buf: BytesMut,
+  config: BcsConfig,
+  done: bool,
+  _marker: PhantomData<fn() -> T>,
+}
+
+impl<S, T> FromRequest<S> for BcsFrames<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if !is_identity(req.headers().get(header::CONTENT_ENCODING))
+      || text_transport(req.headers(), &config).is_some()
+    {
+      return Err(BcsRejection::UnsupportedEncoding);
+    }
+    if bcs_content_type(req.headers(), config.content_type_check) {
+      let body = match config.body_limit {
+        Some(limit) => req.map(|body| limit_body(body, limit)).into_limited_body(),
+        None => req.into_limited_body(),
+      };
+      Ok(BcsFrames {
+        stream: body.into_data_stream(),
+        buf: BytesMut::new(),
+        config,
+        done: false,
+        _marker: PhantomData,
+      })
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
+
+impl<T> BcsFrames<T> {
+  fn next_frame(&mut self) -> Result<Option<Bytes>, BcsRejection> {
+    let Some((len, header_len)) = read_uleb128(&self.buf)? else {
+      return Ok(None);
+    };
+    if let Some(limit) = self.config.frame_limit
+      && len > limit
+    {
+      return Err(BcsRejection::FrameTooLarge { limit });
+    }
+    if self.buf.len() - header_len < len {
+      return Ok(None);
+    }
+
+    self.buf.advance(header_len);
+    Ok(Some(self.buf.split_to(len).freeze()))
+  }
+
+  fn body_error(&self, err: axum_core::Error) -> BcsRejection {
+    match self.config.body_limit {
+      Some(limit) if exceeded_body_limit(&err) => BcsRejection::PayloadTooLarge { limit },
+      _ => BcsRejection::StreamRead(err),
+    }
+  }
+}
+
+impl<T> Stream for BcsFrames<T>
+where
+  T: DeserializeOwned,
+{
+  type Item = Result<T, BcsRejection>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = self.get_mut();
+    loop {
+      if this.done {
+        return Poll::Ready(None);
+      }
+
+      match this.next_frame() {
+        Ok(Some(frame)) => return Poll::Ready(Some(decode(&frame, &this.config))),
+        Ok(None) => {}
+        Err(err) => {
+          this.done = true;
+          return Poll::Ready(Some(Err(err)));
+        }
+      }
+
+      match ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+        Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+        Some(Err(err)) => {
+          this.done = true;
+          return Poll::Ready(Some(Err(this.body_error(err))));
+        }
+        None => {
+          this.done = true;
+          if !this.buf.is_empty() {
+            return Poll::Ready(Some(Err(bcs::Error::Eof.into())));
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Reads a ULEB128-encoded length, returning it with the number of bytes it spans, or
+/// `None` if `buf` ends before the length does.
+pub(crate) fn read_uleb128(buf: &[u8]) -> Result<Option<(usize, usize)>, bcs::Error> {
+  let mut value: u64 = 0;
+  for (index, byte) in buf.iter().take(5).enumerate() {
+    let digit = byte & 0x7f;
+    value |= u64::from(digit) << (7 * index);
+    if digit == *byte {
+      if index > 0 && digit == 0 {
+        return Err(bcs::Error::NonCanonicalUleb128Encoding);
+      }
+      return u32::try_from(value)
+        .map(|value| Some((value as usize, index + 1)))
+        .map_err(|_| bcs::Error::IntegerOverflowDuringUleb128Decoding);
+    }
+  }
+
+  if buf.len() >= 5 {
+    Err(bcs::Error::IntegerOverflowDuringUleb128Decoding)
+  } else {
+    Ok(None)
+  }
+}
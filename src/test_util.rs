@@ -0,0 +1,116 @@
+//! Helpers for calling BCS handlers in-process from tests.
+//!
+//! The assertion helpers panic with the response body in the message when a check fails.
+
+use std::{fmt::Debug, future::poll_fn};
+
+use axum_core::{
+  body::Body,
+  extract::Request,
+  response::Response,
+};
+use bytes::Bytes;
+use http::{HeaderValue, Method, StatusCode, header};
+use http_body_util::BodyExt;
+use serde::{Serialize, de::DeserializeOwned};
+use tower_service::Service;
+
+use crate::{APPLICATION_BCS, BcsConfig, BcsErrorBody, BcsRejection, ContentTypeCheck, bcs_content_type, decode};
+
+/// Builds a request with `value` as its BCS body.
+pub fn bcs_request<T>(method: Method, uri: &str, value: &T) -> Request
+where
+  T: Serialize + ?Sized,
+{
+  let buf = bcs::to_bytes(value).expect("failed to encode request body");
+  http::Request::builder()
+    .method(method)
+    .uri(uri)
+    .header(header::CONTENT_TYPE, HeaderValue::from_static(APPLICATION_BCS))
+    .body(Body::from(buf))
+    .expect("failed to build request")
+}
+
+/// Asserts the status and BCS content type of `response`, then decodes its body.
+pub async fn assert_bcs_response<T>(response: Response, status: StatusCode) -> T
+where
+  T: DeserializeOwned,
+{
+  let (parts, bytes) = into_parts(response).await;
+  assert_eq!(parts.status, status, "unexpected status, body: {bytes:?}");
+  assert!(
+    bcs_content_type(&parts.headers, ContentTypeCheck::Strict),
+    "expected a BCS response, got content type {:?}",
+    parts.headers.get(header::CONTENT_TYPE),
+  );
+
+  match decode(&bytes, &BcsConfig::default()) {
+    Ok(value) => value,
+    Err(err) => panic!("failed to decode response body: {err}"),
+  }
+}
+
+/// Asserts that `response` is the rejection `expected` produces.
+///
+/// Plain-text rejections are compared by body text; [`BcsErrorResponse`](crate::BcsErrorResponse)
+/// and `ProblemJson` bodies by their error code.
+pub async fn assert_bcs_rejection(response: Response, expected: &BcsRejection) {
+  let (parts, bytes) = into_parts(response).await;
+  assert_eq!(parts.status, expected.status(), "unexpected status, body: {bytes:?}");
+
+  let content_type = parts
+    .headers
+    .get(header::CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .unwrap_or_default();
+  match content_type {
+    APPLICATION_BCS => {
+      let body: BcsErrorBody = bcs::from_bytes(&bytes).expect("failed to decode error body");
+      assert_eq!(body.code, expected.code());
+    }
+    #[cfg(feature = "json")]
+    "application/problem+json" => {
+      let body: serde_json::Value = serde_json::from_slice(&bytes).expect("failed to decode problem body");
+      assert_eq!(body["code"], expected.code());
+    }
+    _ => assert_eq!(String::from_utf8_lossy(&bytes), expected.body_text()),
+  }
+}
+
+/// Calls a service with BCS requests and decodes BCS responses in one step.
+pub trait BcsServiceExt {
+  /// Sends `value` as a BCS body, asserts a successful status and decodes the response.
+  fn call_bcs<T, U>(&mut self, method: Method, uri: &str, value: &T) -> impl Future<Output = U>
+  where
+    T: Serialize + ?Sized,
+    U: DeserializeOwned;
+}
+
+impl<S> BcsServiceExt for S
+where
+  S: Service<Request, Response = Response>,
+  S::Error: Debug,
+{
+  async fn call_bcs<T, U>(&mut self, method: Method, uri: &str, value: &T) -> U
+  where
+    T: Serialize + ?Sized,
+    U: DeserializeOwned,
+  {
+    let request = bcs_request(method, uri, value);
+    poll_fn(|cx| self.poll_ready(cx)).await.expect("service is not ready");
+    let response = self.call(request).await.expect("service call failed");
+    let status = response.status();
+    assert!(status.is_success(), "unexpected status {status}");
+    assert_bcs_response(response, status).await
+  }
+}
+
+async fn into_parts(response: Response) -> (http::response::Parts, Bytes) {
+  let (parts, body) = response.into_parts();
+  let bytes = body
+    .collect()
+    .await
+    .expect("failed to read response body")
+    .to_bytes();
+  (parts, bytes)
+}
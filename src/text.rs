@@ -0,0 +1,107 @@
+#[cfg(feature = "text")]
+use base64::{
+  Engine,
+  alphabet,
+  engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use http::HeaderMap;
+#[cfg(feature = "text")]
+use http::{HeaderName, header};
+
+use crate::BcsConfig;
+#[cfg(feature = "text")]
+use crate::BcsRejection;
+
+/// Text encoding for BCS values carried where raw bytes do not fit, such as query strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TextEncoding {
+  /// Lowercase hex; uppercase and a `0x` prefix are accepted when decoding.
+  Hex,
+  /// URL-safe base64 without padding; padding is accepted when decoding.
+  #[default]
+  Base64Url,
+  /// Standard base64 with padding; unpadded input is accepted when decoding.
+  Base64,
+}
+
+#[cfg(feature = "text")]
+const BASE64_URL: GeneralPurpose = GeneralPurpose::new(
+  &alphabet::URL_SAFE,
+  GeneralPurposeConfig::new()
+    .with_encode_padding(false)
+    .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[cfg(feature = "text")]
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+  &alphabet::STANDARD,
+  GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[cfg(feature = "text")]
+const CONTENT_TRANSFER_ENCODING: HeaderName = HeaderName::from_static("content-transfer-encoding");
+
+#[cfg(feature = "text")]
+impl TextEncoding {
+  pub fn encode(self, bytes: impl AsRef<[u8]>) -> String {
+    match self {
+      TextEncoding::Hex => hex::encode(bytes),
+      TextEncoding::Base64Url => BASE64_URL.encode(bytes),
+      TextEncoding::Base64 => BASE64.encode(bytes),
+    }
+  }
+
+  /// Decodes `text`, failing with [`BcsRejection::InvalidTextEncoding`].
+  pub fn decode(self, text: impl AsRef<[u8]>) -> Result<Vec<u8>, BcsRejection> {
+    let text = text.as_ref();
+    match self {
+      TextEncoding::Hex => hex::decode(text.strip_prefix(b"0x").unwrap_or(text)).ok(),
+      TextEncoding::Base64Url => BASE64_URL.decode(text).ok(),
+      TextEncoding::Base64 => BASE64.decode(text).ok(),
+    }
+    .ok_or(BcsRejection::InvalidTextEncoding)
+  }
+
+  pub(crate) fn as_str(self) -> &'static str {
+    match self {
+      TextEncoding::Hex => "hex",
+      TextEncoding::Base64Url => "base64url",
+      TextEncoding::Base64 => "base64",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    [TextEncoding::Hex, TextEncoding::Base64Url, TextEncoding::Base64]
+      .into_iter()
+      .find(|encoding| name.trim().eq_ignore_ascii_case(encoding.as_str()))
+  }
+}
+
+/// The text encoding of a request body, if [`BcsConfig::text_bodies`] is on and the request
+/// is marked as text.
+#[cfg(feature = "text")]
+pub(crate) fn text_transport(headers: &HeaderMap, config: &BcsConfig) -> Option<TextEncoding> {
+  if !config.text_bodies {
+    return None;
+  }
+
+  let from_header = headers
+    .get(CONTENT_TRANSFER_ENCODING)
+    .and_then(|value| value.to_str().ok())
+    .and_then(TextEncoding::from_name);
+  from_header.or_else(|| {
+    let mime = headers
+      .get(header::CONTENT_TYPE)?
+      .to_str()
+      .ok()?
+      .parse::<mime::Mime>()
+      .ok()?;
+    TextEncoding::from_name(mime.suffix()?.as_str())
+  })
+}
+
+#[cfg(not(feature = "text"))]
+pub(crate) fn text_transport(_headers: &HeaderMap, _config: &BcsConfig) -> Option<TextEncoding> {
+  None
+}
@@ -0,0 +1,96 @@
+use std::{
+  marker::PhantomData,
+  ops::{Deref, DerefMut},
+};
+
+use axum_core::{
+  extract::FromRequestParts,
+  response::{IntoResponseParts, ResponseParts},
+};
+use http::{HeaderName, HeaderValue, StatusCode, request::Parts};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{BcsRejection, TextEncoding, decode, params::parts_config};
+
+/// Marker type naming the header a [`BcsHeader`] is read from and written to.
+pub trait BcsHeaderName {
+  const NAME: HeaderName;
+}
+
+/// A BCS value carried as standard base64 in the header named by `N`.
+///
+/// As an extractor it decodes the header with the same limits as [`Bcs`](crate::Bcs), with
+/// padding optional; as a response part it sets the header.
+pub struct BcsHeader<T, N>(pub T, pub PhantomData<N>);
+
+impl<T, N> BcsHeader<T, N> {
+  pub fn new(value: T) -> Self {
+    Self(value, PhantomData)
+  }
+
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+impl<S, T, N> FromRequestParts<S> for BcsHeader<T, N>
+where
+  T: DeserializeOwned,
+  N: BcsHeaderName,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request_parts(parts: &mut Parts, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = parts_config(parts);
+    let value = parts
+      .headers
+      .get(N::NAME)
+      .ok_or_else(|| BcsRejection::MissingHeader {
+        name: N::NAME.to_string(),
+      })?;
+    let bytes = TextEncoding::Base64.decode(value.as_bytes().trim_ascii())?;
+    if let Some(limit) = config.body_limit
+      && bytes.len() > limit
+    {
+      return Err(BcsRejection::PayloadTooLarge { limit });
+    }
+    decode(&bytes, &config).map(BcsHeader::new)
+  }
+}
+
+impl<T, N> IntoResponseParts for BcsHeader<T, N>
+where
+  T: Serialize,
+  N: BcsHeaderName,
+{
+  type Error = (StatusCode, String);
+
+  fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+    let buf = bcs::to_bytes(&self.0).map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    let value = HeaderValue::try_from(TextEncoding::Base64.encode(buf))
+      .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    res.headers_mut().insert(N::NAME, value);
+    Ok(res)
+  }
+}
+
+impl<T, N> Deref for BcsHeader<T, N> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<T, N> DerefMut for BcsHeader<T, N> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.0
+  }
+}
+
+impl<T, N> From<T> for BcsHeader<T, N> {
+  fn from(inner: T) -> Self {
+    Self::new(inner)
+  }
+}
@@ -0,0 +1,166 @@
+use std::ops::{Deref, DerefMut};
+
+use axum_core::extract::{FromRequest, Request};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// A failed check on one field of a decoded value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BcsFieldError {
+  /// Path to the field, e.g. `amount` or `outputs[0].address`.
+  pub field: String,
+  /// A stable identifier for the failed check.
+  pub code: String,
+  pub message: Option<String>,
+}
+
+impl BcsFieldError {
+  pub fn new(field: impl Into<String>, code: impl Into<String>) -> Self {
+    Self {
+      field: field.into(),
+      code: code.into(),
+      message: None,
+    }
+  }
+
+  pub fn with_message(mut self, message: impl Into<String>) -> Self {
+    self.message = Some(message.into());
+    self
+  }
+}
+
+/// Invariants checked on a value after it is decoded by [`ValidatedBcs`].
+pub trait BcsValidate {
+  /// Checks the value, usually failing with [`BcsRejection::Validation`].
+  ///
+  /// With the `validator` feature, `validator::ValidationErrors` converts into that
+  /// rejection, so a derived `Validate` impl can be forwarded with `?`.
+  fn validate(&self) -> Result<(), BcsRejection>;
+}
+
+/// Extractor that decodes like [`Bcs`](crate::Bcs) and then runs [`BcsValidate::validate`].
+pub struct ValidatedBcs<T>(pub T);
+
+impl<S, T> FromRequest<S> for ValidatedBcs<T>
+where
+  T: DeserializeOwned + BcsValidate,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      let value: T = decode(&bytes, &config)?;
+      value.validate()?;
+      Ok(ValidatedBcs(value))
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
+
+impl<T> Deref for ValidatedBcs<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<T> DerefMut for ValidatedBcs<T> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.0
+  }
+}
+
+/// Extractor that decodes like [`Bcs`](crate::Bcs) and then runs the `validator` crate's
+/// [`Validate`](validator::Validate), so derived rules need no [`BcsValidate`] impl.
+#[cfg(feature = "validator")]
+pub struct ValidBcs<T>(pub T);
+
+#[cfg(feature = "validator")]
+impl<S, T> FromRequest<S> for ValidBcs<T>
+where
+  T: DeserializeOwned + validator::Validate,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      let value: T = decode(&bytes, &config)?;
+      value.validate()?;
+      Ok(ValidBcs(value))
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
+
+#[cfg(feature = "validator")]
+impl<T> Deref for ValidBcs<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+#[cfg(feature = "validator")]
+impl<T> DerefMut for ValidBcs<T> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.0
+  }
+}
+
+pub(crate) fn format_field_errors(errors: &[BcsFieldError]) -> String {
+  errors
+    .iter()
+    .map(|error| match &error.message {
+      Some(message) => format!("{}: {}", error.field, message),
+      None => format!("{}: {}", error.field, error.code),
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for BcsRejection {
+  fn from(errors: validator::ValidationErrors) -> Self {
+    let mut fields = Vec::new();
+    flatten_validation_errors(&errors, "", &mut fields);
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+    BcsRejection::Validation(fields)
+  }
+}
+
+#[cfg(feature = "validator")]
+fn flatten_validation_errors(errors: &validator::ValidationErrors, prefix: &str, out: &mut Vec<BcsFieldError>) {
+  use validator::ValidationErrorsKind;
+
+  for (field, kind) in errors.errors() {
+    let path = if prefix.is_empty() {
+      field.to_string()
+    } else {
+      format!("{prefix}.{field}")
+    };
+    match kind {
+      ValidationErrorsKind::Field(errors) => out.extend(errors.iter().map(|error| BcsFieldError {
+        field: path.clone(),
+        code: error.code.to_string(),
+        message: error.message.as_ref().map(|message| message.to_string()),
+      })),
+      ValidationErrorsKind::Struct(errors) => flatten_validation_errors(errors, &path, out),
+      ValidationErrorsKind::List(items) => {
+        for (index, errors) in items {
+          flatten_validation_errors(errors, &format!("{path}[{index}]"), out);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,173 @@
+use std::convert::Infallible;
+
+use axum_core::{
+  extract::{FromRequest, FromRequestParts, Request},
+  response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use http::{HeaderName, HeaderValue, header, request::Parts};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+  APPLICATION_BCS, Bcs, BcsBytes, BcsConfig, BcsRejection, bcs_body_type, bcs_config, decode, read_body,
+  stream::{read_uleb128, write_uleb128},
+};
+
+/// Header carrying the schema version of a BCS body.
+pub const X_BCS_SCHEMA_VERSION: HeaderName = HeaderName::from_static("x-bcs-schema-version");
+
+/// Where [`VersionedBcs`] reads the schema version of a request from.
+///
+/// It is also an extractor for the route's setting, whose
+/// [`respond`](SchemaVersionSource::respond) tags a response the same way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaVersionSource {
+  /// The [`X_BCS_SCHEMA_VERSION`] header. Requests without it are taken to use the current
+  /// version.
+  #[default]
+  Header,
+  /// A ULEB128-encoded version ahead of the BCS payload. The header is ignored.
+  Prefix,
+}
+
+impl SchemaVersionSource {
+  /// Wraps `value` in a response carrying its version in the header and, in
+  /// [`Prefix`](SchemaVersionSource::Prefix) mode, ahead of the body as well.
+  pub fn respond<T>(self, value: T) -> VersionedResponse<T> {
+    VersionedResponse { source: self, value }
+  }
+}
+
+impl<S> FromRequestParts<S> for SchemaVersionSource
+where
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, _s: &S) -> Result<Self, Self::Rejection> {
+    Ok(
+      parts
+        .extensions
+        .get::<BcsConfig>()
+        .map_or(SchemaVersionSource::default(), |config| config.schema_version_source),
+    )
+  }
+}
+
+/// A type whose BCS layout is versioned.
+///
+/// Bump [`VERSION`](BcsMigrate::VERSION) whenever the layout changes and decode the older
+/// layouts in [`migrate`](BcsMigrate::migrate).
+pub trait BcsMigrate: DeserializeOwned {
+  /// The schema version of the current layout.
+  const VERSION: u32;
+
+  /// Decodes a payload written with an older or newer schema `version` and upgrades it.
+  ///
+  /// The payload is decoded with the route's limits via [`BcsBytes::decode`]. The default
+  /// rejects every version other than the current one.
+  fn migrate(version: u32, payload: &BcsBytes) -> Result<Self, BcsRejection> {
+    let _ = payload;
+    Err(BcsRejection::UnsupportedSchemaVersion { version })
+  }
+}
+
+/// Extractor and response for BCS bodies tagged with a schema version.
+///
+/// Requests with the current [`BcsMigrate::VERSION`] decode like [`Bcs`]; any other version
+/// goes through [`BcsMigrate::migrate`]. The version is read from the source set by
+/// [`BcsConfig::schema_version_source`]. Responses carry the current version in the
+/// [`X_BCS_SCHEMA_VERSION`] header only; routes in prefix mode answer through
+/// [`SchemaVersionSource::respond`] so the body is prefixed too.
+pub struct VersionedBcs<T>(pub T);
+
+impl<S, T> FromRequest<S> for VersionedBcs<T>
+where
+  T: BcsMigrate,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if !bcs_body_type(req.headers(), &config) {
+      return Err(BcsRejection::MissingContentType);
+    }
+
+    let header_version = match (config.schema_version_source, req.headers().get(X_BCS_SCHEMA_VERSION)) {
+      (SchemaVersionSource::Header, Some(value)) => Some(
+        value
+          .to_str()
+          .ok()
+          .and_then(|value| value.parse::<u32>().ok())
+          .ok_or(BcsRejection::InvalidSchemaVersion)?,
+      ),
+      _ => None,
+    };
+
+    let bytes = read_body(req, _s, &config).await?;
+    let (version, payload) = match config.schema_version_source {
+      SchemaVersionSource::Header => (header_version.unwrap_or(T::VERSION), bytes),
+      SchemaVersionSource::Prefix => match read_uleb128(&bytes) {
+        Ok(Some((version, len))) => (version as u32, bytes.slice(len..)),
+        _ => return Err(BcsRejection::InvalidSchemaVersion),
+      },
+    };
+
+    if version == T::VERSION {
+      decode(&payload, &config).map(VersionedBcs)
+    } else {
+      T::migrate(version, &BcsBytes::new(payload, config)).map(VersionedBcs)
+    }
+  }
+}
+
+impl<T> IntoResponse for VersionedBcs<T>
+where
+  T: BcsMigrate + Serialize,
+{
+  fn into_response(self) -> Response {
+    let mut response = Bcs(self.0).into_response();
+    if response.status().is_success() {
+      response
+        .headers_mut()
+        .insert(X_BCS_SCHEMA_VERSION, HeaderValue::from(T::VERSION));
+    }
+    response
+  }
+}
+
+/// Response built by [`SchemaVersionSource::respond`].
+pub struct VersionedResponse<T> {
+  source: SchemaVersionSource,
+  value: T,
+}
+
+impl<T> IntoResponse for VersionedResponse<T>
+where
+  T: BcsMigrate + Serialize,
+{
+  fn into_response(self) -> Response {
+    let mut response = match self.source {
+      SchemaVersionSource::Header => Bcs(self.value).into_response(),
+      SchemaVersionSource::Prefix => match bcs::to_bytes(&self.value) {
+        Ok(payload) => {
+          let mut buf = Vec::with_capacity(payload.len() + 5);
+          write_uleb128(&mut buf, T::VERSION as usize);
+          buf.extend_from_slice(&payload);
+          (
+            [(header::CONTENT_TYPE, HeaderValue::from_static(APPLICATION_BCS))],
+            Bytes::from(buf),
+          ).into_response()
+        }
+        Err(_) => Bcs(self.value).into_response(),
+      },
+    };
+    if response.status().is_success() {
+      response
+        .headers_mut()
+        .insert(X_BCS_SCHEMA_VERSION, HeaderValue::from(T::VERSION));
+    }
+    response
+  }
+}
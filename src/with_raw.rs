@@ -0,0 +1,30 @@
+use axum_core::extract::{FromRequest, Request};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+
+use crate::{BcsRejection, bcs_body_type, bcs_config, decode, read_body};
+
+/// Extractor that decodes like [`Bcs`](crate::Bcs) and also keeps the body it decoded from.
+///
+/// The bytes share the request buffer, so keeping them costs no copy. For compressed
+/// requests they are the decompressed body.
+pub struct BcsWithRaw<T>(pub T, pub Bytes);
+
+impl<S, T> FromRequest<S> for BcsWithRaw<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = BcsRejection;
+
+  async fn from_request(req: Request, _s: &S) -> Result<Self, Self::Rejection> {
+    let config = bcs_config(&req);
+    if bcs_body_type(req.headers(), &config) {
+      let bytes = read_body(req, _s, &config).await?;
+      let value = decode(&bytes, &config)?;
+      Ok(BcsWithRaw(value, bytes))
+    } else {
+      Err(BcsRejection::MissingContentType)
+    }
+  }
+}
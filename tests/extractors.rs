@@ -0,0 +1,475 @@
+use std::io::Write;
+
+use axum::{
+  Extension, Router,
+  body::Body,
+  extract::DefaultBodyLimit,
+  routing::{get, post},
+};
+use axum_bcs::{
+  Bcs, BcsBytes, BcsConfig, BcsDecodeErrorKind, BcsErrorBody, BcsErrorKind, BcsErrorResponse, BcsFieldError, BcsFrames,
+  BcsHashed, BcsHeader, BcsHeaderName, BcsMigrate, BcsPath, BcsPrefix, BcsQuery, BcsRejection, BcsValidate,
+  Negotiate, SchemaVersionSource, TextEncoding, ValidatedBcs, VersionedBcs, X_BCS_SCHEMA_VERSION, X_CONTENT_DIGEST,
+  BcsStream, CanonicalBcs, SignedBcs, X_BCS_PUBLIC_KEY, X_BCS_SIGNATURE,
+  sha2::{Digest, Sha256},
+  ed25519_dalek::{Signer, SigningKey},
+  test_util::{BcsServiceExt, assert_bcs_rejection, assert_bcs_response, bcs_request},
+};
+use futures_util::{StreamExt, stream};
+use http::{Method, Request, StatusCode, header};
+use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Item {
+  id: u32,
+  name: String,
+}
+
+/// A flag that accepts any non-zero byte as `true` but always encodes `true` as `1`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
+struct Flag(bool);
+
+impl From<u8> for Flag {
+  fn from(value: u8) -> Self {
+    Flag(value != 0)
+  }
+}
+
+impl From<Flag> for u8 {
+  fn from(flag: Flag) -> Self {
+    flag.0 as u8
+  }
+}
+
+impl BcsValidate for Item {
+  fn validate(&self) -> Result<(), BcsRejection> {
+    if self.name.is_empty() {
+      return Err(BcsRejection::Validation(vec![BcsFieldError::new("name", "empty")]));
+    }
+    Ok(())
+  }
+}
+
+/// Version 1 of [`Item`] had no name.
+impl BcsMigrate for Item {
+  const VERSION: u32 = 2;
+
+  fn migrate(version: u32, payload: &BcsBytes) -> Result<Self, BcsRejection> {
+    match version {
+      1 => payload.decode().map(|id| Item { id, name: String::new() }),
+      _ => Err(BcsRejection::UnsupportedSchemaVersion { version }),
+    }
+  }
+}
+
+/// A struct nested `depth` levels deep.
+#[derive(Debug, Serialize, Deserialize)]
+struct Nested(Option<Box<Nested>>);
+
+impl Nested {
+  fn new(depth: usize) -> Self {
+    (1..depth).fold(Nested(None), |inner, _| Nested(Some(Box::new(inner))))
+  }
+}
+
+struct ItemHeader;
+
+impl BcsHeaderName for ItemHeader {
+  const NAME: http::HeaderName = http::HeaderName::from_static("x-item");
+}
+
+fn item() -> Item {
+  Item { id: 7, name: "item".into() }
+}
+
+fn signed_request(key: &SigningKey, domain: &[u8], payload: &[u8]) -> Request<Body> {
+  let signature = key.sign(&[domain, payload].concat());
+  let mut request = bcs_request(Method::POST, "/", &0u8);
+  *request.body_mut() = Body::from(payload.to_vec());
+  let headers = request.headers_mut();
+  headers.insert(X_BCS_PUBLIC_KEY, hex::encode(key.verifying_key()).parse().unwrap());
+  headers.insert(X_BCS_SIGNATURE, hex::encode(signature.to_bytes()).parse().unwrap());
+  request
+}
+
+#[tokio::test]
+async fn signed_bcs_verifies_signatures() {
+  let app = Router::new()
+    .route("/", post(|SignedBcs(item, _): SignedBcs<Item>| async move { Bcs(item) }))
+    .layer(Extension(BcsConfig::new().signature_domain("test-domain")));
+  let key = SigningKey::from_bytes(&[7; 32]);
+  let item = Item { id: 1, name: "signed".into() };
+  let payload = bcs::to_bytes(&item).unwrap();
+
+  let response = app.clone().oneshot(signed_request(&key, b"test-domain", &payload)).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item);
+
+  let response = app.clone().oneshot(signed_request(&key, b"other-domain", &payload)).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidSignature).await;
+
+  let mut request = signed_request(&key, b"test-domain", &payload);
+  request.headers_mut().remove(X_BCS_SIGNATURE);
+  let response = app.oneshot(request).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidSignature).await;
+}
+
+#[tokio::test]
+async fn decompression_is_capped() {
+  let app = Router::new()
+    .route("/", post(|Bcs(bytes): Bcs<Vec<u8>>| async move { Bcs(bytes.len() as u64) }))
+    .layer(Extension(BcsConfig::new().decompression_limit(1024)));
+  let gzip_request = |len: usize| {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&bcs::to_bytes(&vec![0u8; len]).unwrap()).unwrap();
+    let mut request = bcs_request(Method::POST, "/", &0u8);
+    *request.body_mut() = Body::from(encoder.finish().unwrap());
+    request.headers_mut().insert(header::CONTENT_ENCODING, "gzip".parse().unwrap());
+    request
+  };
+
+  let response = app.clone().oneshot(gzip_request(512)).await.unwrap();
+  assert_eq!(assert_bcs_response::<u64>(response, StatusCode::OK).await, 512);
+
+  let response = app.oneshot(gzip_request(4096)).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::DecompressedTooLarge { limit: 1024 }).await;
+}
+
+#[tokio::test]
+async fn canonical_bcs_rejects_other_encodings() {
+  let app = Router::new().route(
+    "/",
+    post(|CanonicalBcs(flags): CanonicalBcs<Vec<Flag>>| async move { Bcs(flags.len() as u64) }),
+  );
+
+  let len: u64 = app.clone().call_bcs(Method::POST, "/", &vec![0u8, 1]).await;
+  assert_eq!(len, 2);
+
+  let response = app.oneshot(bcs_request(Method::POST, "/", &vec![0u8, 2])).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::NonCanonical { offset: 2 }).await;
+}
+
+/// Posts a raw body to a [`Bcs<Item>`] route that answers rejections with [`BcsErrorResponse`].
+async fn decode_error(payload: &[u8]) -> BcsErrorBody {
+  let app = Router::new().route(
+    "/",
+    post(|item: Result<Bcs<Item>, BcsRejection>| async move { item.map_err(BcsErrorResponse) }),
+  );
+  let mut request = bcs_request(Method::POST, "/", &0u8);
+  *request.body_mut() = Body::from(payload.to_vec());
+  let response = app.oneshot(request).await.unwrap();
+  assert_bcs_response(response, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn decode_errors_report_offsets() {
+  let body = decode_error(&[1, 0, 0, 0, 3, b'h']).await;
+  assert_eq!(body.kind, BcsErrorKind::BcsError(BcsDecodeErrorKind::Eof));
+  assert_eq!(body.offset, Some(6));
+
+  let body = decode_error(&[1, 0, 0, 0, 1, 0xff]).await;
+  assert_eq!(body.kind, BcsErrorKind::BcsError(BcsDecodeErrorKind::Utf8));
+  assert_eq!(body.offset, Some(5));
+
+  let body = decode_error(&[1, 0, 0, 0, 0x80, 0x00]).await;
+  assert_eq!(body.kind, BcsErrorKind::BcsError(BcsDecodeErrorKind::NonCanonicalUleb128Encoding));
+  assert_eq!(body.offset, Some(5));
+
+  let body = decode_error(&[1, 0, 0, 0, 1, b'h', 9, 9]).await;
+  assert_eq!(body.kind, BcsErrorKind::TrailingBytes { consumed: 6, remaining: 2 });
+}
+
+#[tokio::test]
+async fn oversized_length_prefix_is_rejected_without_allocating() {
+  // The string claims 2 GiB but the body ends right after the prefix.
+  let body = decode_error(&[1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0x07]).await;
+  assert_eq!(body.kind, BcsErrorKind::BcsError(BcsDecodeErrorKind::Eof));
+  assert_eq!(body.offset, Some(9));
+}
+
+#[tokio::test]
+async fn body_limit_rejects_large_payloads() {
+  let app = Router::new()
+    .route("/", post(|Bcs(bytes): Bcs<Vec<u8>>| async move { Bcs(bytes.len() as u64) }))
+    .layer(Extension(BcsConfig::new().body_limit(16)));
+
+  let len: u64 = app.clone().call_bcs(Method::POST, "/", &vec![1u8; 8]).await;
+  assert_eq!(len, 8);
+
+  let response = app.oneshot(bcs_request(Method::POST, "/", &vec![1u8; 32])).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::PayloadTooLarge { limit: 16 }).await;
+}
+
+#[tokio::test]
+async fn smaller_default_body_limit_is_reported_as_is() {
+  let app = Router::new()
+    .route("/", post(|Bcs(bytes): Bcs<Vec<u8>>| async move { Bcs(bytes.len() as u64) }))
+    .layer(DefaultBodyLimit::max(8))
+    .layer(Extension(BcsConfig::new().body_limit(16)));
+
+  let response = app.oneshot(bcs_request(Method::POST, "/", &vec![1u8; 12])).await.unwrap();
+  assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+  let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+  assert_eq!(&body[..], b"Failed to buffer the request body: length limit exceeded");
+}
+
+#[tokio::test]
+async fn frames_round_trip_through_bcs_stream() {
+  let items: Vec<Item> = (0..5)
+    .map(|id| Item {
+      id,
+      name: "x".repeat(id as usize * 100),
+    })
+    .collect();
+  let sent = items.clone();
+  let app = Router::new()
+    .route("/out", post(move || async move { BcsStream::new(stream::iter(sent)) }))
+    .route(
+      "/in",
+      post(|frames: BcsFrames<Item>| async move {
+        let items: Result<Vec<Item>, BcsRejection> = frames.collect::<Vec<_>>().await.into_iter().collect();
+        items.map(Bcs)
+      }),
+    );
+
+  let response = app.clone().oneshot(bcs_request(Method::POST, "/out", &())).await.unwrap();
+  let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+  let mut request = bcs_request(Method::POST, "/in", &());
+  *request.body_mut() = Body::from(body);
+  let response = app.oneshot(request).await.unwrap();
+  assert_eq!(assert_bcs_response::<Vec<Item>>(response, StatusCode::OK).await, items);
+}
+
+#[tokio::test]
+async fn frames_apply_both_body_limits() {
+  let app = |limit: usize| {
+    Router::new()
+      .route(
+        "/",
+        post(|frames: BcsFrames<Vec<u8>>| async move {
+          let frames: Result<Vec<_>, BcsRejection> = frames.collect::<Vec<_>>().await.into_iter().collect();
+          frames.map(|frames| Bcs(frames.len() as u64))
+        }),
+      )
+      .layer(DefaultBodyLimit::max(64))
+      .layer(Extension(BcsConfig::new().body_limit(limit)))
+  };
+  let request = || {
+    let mut request = bcs_request(Method::POST, "/", &());
+    *request.body_mut() = Body::from([vec![100], vec![0u8; 100]].concat());
+    request
+  };
+
+  let response = app(16).oneshot(request()).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::PayloadTooLarge { limit: 16 }).await;
+
+  let response = app(1024).oneshot(request()).await.unwrap();
+  assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+  let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+  assert!(body.starts_with(b"Failed to read the request body"), "{body:?}");
+}
+
+#[tokio::test]
+async fn container_depth_limit_is_enforced() {
+  let app = Router::new()
+    .route("/", post(|Bcs(_): Bcs<Nested>| async { Bcs(()) }))
+    .layer(Extension(BcsConfig::new().max_container_depth(4)));
+
+  let () = app.clone().call_bcs(Method::POST, "/", &Nested::new(4)).await;
+
+  let response = app.oneshot(bcs_request(Method::POST, "/", &Nested::new(5))).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::ContainerDepthExceeded { limit: 4 }).await;
+}
+
+#[tokio::test]
+async fn prefix_keeps_trailing_bytes() {
+  let app = Router::new().route(
+    "/",
+    post(|BcsPrefix(item, rest): BcsPrefix<Item>| async move { Bcs((item, rest.to_vec())) }),
+  );
+  let mut request = bcs_request(Method::POST, "/", &0u8);
+  *request.body_mut() = Body::from([bcs::to_bytes(&item()).unwrap(), vec![9, 9]].concat());
+
+  let response = app.clone().oneshot(request).await.unwrap();
+  assert_eq!(
+    assert_bcs_response::<(Item, Vec<u8>)>(response, StatusCode::OK).await,
+    (item(), vec![9, 9]),
+  );
+
+  let mut request = bcs_request(Method::POST, "/", &0u8);
+  *request.body_mut() = Body::from(vec![1, 0, 0, 0, 3, b'h']);
+  let response = app.oneshot(request).await.unwrap();
+  assert_bcs_rejection(
+    response,
+    &BcsRejection::BcsError {
+      offset: Some(6),
+      source: bcs::Error::Eof,
+    },
+  )
+  .await;
+}
+
+#[tokio::test]
+async fn negotiate_rejects_unacceptable_formats() {
+  let app = Router::new().route("/", get(|negotiate: Negotiate| async move { negotiate.respond(item()) }));
+  let request = |accept: &str| {
+    Request::get("/")
+      .header(header::ACCEPT, accept)
+      .body(Body::empty())
+      .unwrap()
+  };
+
+  let response = app.clone().oneshot(request("application/x-bcs")).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+
+  let response = app.oneshot(request("text/html")).await.unwrap();
+  assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+  assert_eq!(response.headers()[header::VARY], "accept");
+}
+
+#[tokio::test]
+async fn query_and_path_params_are_decoded() {
+  let app = Router::new()
+    .route("/query", get(|BcsQuery(item, _): BcsQuery<Item>| async move { Bcs(item) }))
+    .route("/path/{bcs}", get(|BcsPath(item, _): BcsPath<Item>| async move { Bcs(item) }))
+    .route("/other/{id}", get(|BcsPath(item, _): BcsPath<Item>| async move { Bcs(item) }));
+  let encoded = TextEncoding::Base64Url.encode(bcs::to_bytes(&item()).unwrap());
+  let get = |uri: String| Request::get(uri).body(Body::empty()).unwrap();
+
+  let response = app.clone().oneshot(get(format!("/query?bcs={encoded}"))).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+  let response = app.clone().oneshot(get(format!("/path/{encoded}"))).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+
+  let missing = BcsRejection::MissingParam { name: "bcs".into() };
+  let response = app.clone().oneshot(get("/query?other=1".into())).await.unwrap();
+  assert_bcs_rejection(response, &missing).await;
+  let response = app.clone().oneshot(get(format!("/other/{encoded}"))).await.unwrap();
+  assert_bcs_rejection(response, &missing).await;
+
+  let response = app.clone().oneshot(get("/query?bcs=*".into())).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidTextEncoding).await;
+  let response = app.oneshot(get("/path/*".into())).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidTextEncoding).await;
+}
+
+#[tokio::test]
+async fn header_values_are_decoded() {
+  let app = Router::new().route(
+    "/",
+    get(|BcsHeader(item, _): BcsHeader<Item, ItemHeader>| async move { Bcs(item) }),
+  );
+  let encoded = TextEncoding::Base64.encode(bcs::to_bytes(&item()).unwrap());
+
+  let request = Request::get("/").header("x-item", encoded).body(Body::empty()).unwrap();
+  let response = app.clone().oneshot(request).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+
+  let response = app.clone().oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::MissingHeader { name: "x-item".into() }).await;
+
+  let request = Request::get("/").header("x-item", "*").body(Body::empty()).unwrap();
+  let response = app.oneshot(request).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidTextEncoding).await;
+}
+
+#[tokio::test]
+async fn text_bodies_are_opt_in() {
+  let route = || Router::new().route("/", post(|Bcs(item): Bcs<Item>| async move { Bcs(item) }));
+  let app = route().layer(Extension(BcsConfig::new().text_bodies(true)));
+  let request = |body: String| {
+    Request::post("/")
+      .header(header::CONTENT_TYPE, "application/x-bcs+hex")
+      .body(Body::from(body))
+      .unwrap()
+  };
+  let encoded = TextEncoding::Hex.encode(bcs::to_bytes(&item()).unwrap());
+
+  let response = app.clone().oneshot(request(encoded.clone())).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+
+  let response = app.oneshot(request("not hex".into())).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidTextEncoding).await;
+
+  let response = route().oneshot(request(encoded)).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::MissingContentType).await;
+}
+
+#[tokio::test]
+async fn versioned_bcs_migrates_known_versions() {
+  let route = || Router::new().route("/", post(|VersionedBcs(item): VersionedBcs<Item>| async move { Bcs(item) }));
+  let app = route();
+  let request = |version: &str, payload: &[u8]| {
+    let mut request = bcs_request(Method::POST, "/", &0u8);
+    *request.body_mut() = Body::from(payload.to_vec());
+    request.headers_mut().insert(X_BCS_SCHEMA_VERSION, version.parse().unwrap());
+    request
+  };
+
+  let response = app.clone().oneshot(request("1", &bcs::to_bytes(&7u32).unwrap())).await.unwrap();
+  let migrated = Item { id: 7, name: String::new() };
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, migrated);
+
+  let response = app.clone().oneshot(request("3", &[])).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::UnsupportedSchemaVersion { version: 3 }).await;
+
+  let response = app.oneshot(request("two", &[])).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidSchemaVersion).await;
+
+  let app = route().layer(Extension(BcsConfig::new().schema_version_source(SchemaVersionSource::Prefix)));
+  let response = app.clone().oneshot(request("2", &[0x80])).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::InvalidSchemaVersion).await;
+
+  let payload = [vec![2], bcs::to_bytes(&item()).unwrap()].concat();
+  let response = app.oneshot(request("1", &payload)).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+}
+
+#[tokio::test]
+async fn hashed_bcs_checks_digests() {
+  let app = Router::new()
+    .route(
+      "/",
+      post(|BcsHashed(item, _): BcsHashed<Item, Sha256>| async move { Bcs(item) }),
+    )
+    .layer(Extension(BcsConfig::new().digest_domain("test-domain").require_digest(true)));
+  let payload = bcs::to_bytes(&item()).unwrap();
+  let request = |digest: Option<String>| {
+    let mut request = bcs_request(Method::POST, "/", &item());
+    if let Some(digest) = digest {
+      request.headers_mut().insert(X_CONTENT_DIGEST, digest.parse().unwrap());
+    }
+    request
+  };
+
+  let digest = Sha256::new().chain_update(b"test-domain").chain_update(&payload).finalize();
+  let response = app.clone().oneshot(request(Some(hex::encode(digest)))).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+
+  let digest = Sha256::digest(&payload);
+  let response = app.clone().oneshot(request(Some(hex::encode(digest)))).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::DigestMismatch).await;
+
+  let response = app.oneshot(request(None)).await.unwrap();
+  assert_bcs_rejection(response, &BcsRejection::MissingDigest).await;
+}
+
+#[tokio::test]
+async fn validated_bcs_runs_validation() {
+  let app = Router::new().route(
+    "/",
+    post(|ValidatedBcs(item): ValidatedBcs<Item>| async move { Bcs(item) }),
+  );
+
+  let response = app.clone().oneshot(bcs_request(Method::POST, "/", &item())).await.unwrap();
+  assert_eq!(assert_bcs_response::<Item>(response, StatusCode::OK).await, item());
+
+  let invalid = Item { id: 7, name: String::new() };
+  let response = app.oneshot(bcs_request(Method::POST, "/", &invalid)).await.unwrap();
+  assert_bcs_rejection(
+    response,
+    &BcsRejection::Validation(vec![BcsFieldError::new("name", "empty")]),
+  )
+  .await;
+}